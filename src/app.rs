@@ -0,0 +1,308 @@
+use ffi::*;
+use std::{
+    mem,
+    os::raw::c_void,
+    ptr,
+    sync::{Arc, Mutex, RwLock, Weak},
+};
+use {Error, WVResult, WebView, WebViewBuilder};
+
+/// Identifies a window of an [`App`].
+///
+/// [`App`]: struct.App.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct WindowId(usize);
+
+type Command = Box<dyn for<'b> FnOnce(&mut App<'b>) -> WVResult + Send>;
+
+/// A handler registered with [`App::on_window_error()`].
+///
+/// [`App::on_window_error()`]: struct.App.html#method.on_window_error
+pub type WindowErrorHandler<'a> = Box<dyn FnMut(WindowId, Error) + 'a>;
+
+/// Lets a blocked event loop be woken up from another thread.
+struct Waker {
+    id: WindowId,
+    inner: *mut CWebView,
+    live: Weak<RwLock<bool>>,
+}
+
+unsafe impl Send for Waker {}
+
+#[derive(Default)]
+struct Shared {
+    commands: Vec<Command>,
+    wakers: Vec<Waker>,
+}
+
+/// The windows of an `App` can have user data of different types.
+trait Window {
+    fn step(&mut self, blocking: bool) -> Option<WVResult>;
+    fn waker(&self, id: WindowId) -> Waker;
+}
+
+impl<'a, T> Window for WebView<'a, T> {
+    fn step(&mut self, blocking: bool) -> Option<WVResult> {
+        self.loop_once(blocking)
+    }
+
+    fn waker(&self, id: WindowId) -> Waker {
+        Waker {
+            id,
+            inner: self.inner.unwrap(),
+            live: self.live(),
+        }
+    }
+}
+
+extern "C" fn ffi_wake_handler(_webview: *mut CWebView, _arg: *mut c_void) {}
+
+/// An application with any number of windows sharing one event loop.
+///
+/// Unlike stepping several [`WebView`]s by hand, the app keeps running until its last window is
+/// closed or [`quit()`] is called. Windows can be opened and closed while it runs, from any
+/// thread through an [`AppHandle`].
+///
+/// # Example
+///
+/// ```ignore
+/// let mut app = App::new();
+/// let handle = app.handle();
+/// app.open(
+///     web_view::builder()
+///         .title("Main window")
+///         .content(Content::Html(HTML))
+///         .user_data(())
+///         .invoke_handler(move |_webview, arg| {
+///             if arg == "open" {
+///                 handle.dispatch(|app| app.open(settings_window()).map(|_| ()))?;
+///             }
+///             Ok(())
+///         }),
+/// )?;
+/// app.run()?;
+/// ```
+///
+/// [`WebView`]: struct.WebView.html
+/// [`quit()`]: #method.quit
+/// [`AppHandle`]: struct.AppHandle.html
+pub struct App<'a> {
+    windows: Vec<(WindowId, Box<dyn Window + 'a>)>,
+    next_id: usize,
+    quit: bool,
+    shared: Arc<Mutex<Shared>>,
+    window_error_handler: Option<WindowErrorHandler<'a>>,
+    window_error: Option<Error>,
+}
+
+impl<'a> Default for App<'a> {
+    fn default() -> Self {
+        App::new()
+    }
+}
+
+impl<'a> App<'a> {
+    /// Creates an app without any windows.
+    pub fn new() -> Self {
+        // If this fails, so does building the first window.
+        unsafe { webview_init() };
+        App {
+            windows: Vec::new(),
+            next_id: 0,
+            quit: false,
+            shared: Arc::new(Mutex::new(Shared::default())),
+            window_error_handler: None,
+            window_error: None,
+        }
+    }
+
+    /// Sets the handler for errors of a window, i.e. an `Err` returned by one of its handlers.
+    ///
+    /// Such a window is closed before the handler is called, and the app keeps running. Without
+    /// a handler, [`run()`] returns the first of these errors once it ends.
+    ///
+    /// [`run()`]: #method.run
+    pub fn on_window_error<F>(&mut self, handler: F)
+    where
+        F: FnMut(WindowId, Error) + 'a,
+    {
+        self.window_error_handler = Some(Box::new(handler));
+    }
+
+    /// Builds a `WebView` and adds it to the app as a new window.
+    pub fn open<T, I, C>(&mut self, builder: WebViewBuilder<'a, T, I, C>) -> WVResult<WindowId>
+    where
+        T: 'a,
+        I: FnMut(&mut WebView<T>, &str) -> WVResult + 'a,
+        C: AsRef<str>,
+    {
+        Ok(self.add(builder.build()?))
+    }
+
+    /// Adds an already built `WebView` to the app as a new window.
+    pub fn add<T: 'a>(&mut self, webview: WebView<'a, T>) -> WindowId {
+        let id = WindowId(self.next_id);
+        self.next_id += 1;
+        self.shared.lock().unwrap().wakers.push(webview.waker(id));
+        self.windows.push((id, Box::new(webview)));
+        id
+    }
+
+    /// Closes a window. Returns `false` if it was already closed.
+    pub fn close(&mut self, id: WindowId) -> bool {
+        match self.windows.iter().position(|&(i, _)| i == id) {
+            Some(index) => {
+                self.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Closes all windows, ending [`run()`].
+    ///
+    /// [`run()`]: #method.run
+    pub fn quit(&mut self) {
+        self.quit = true;
+    }
+
+    /// Returns the IDs of the open windows, in the order they were opened.
+    pub fn window_ids(&self) -> Vec<WindowId> {
+        self.windows.iter().map(|&(id, _)| id).collect()
+    }
+
+    /// Returns a handle to control the app from other threads or from the handlers of its
+    /// windows.
+    pub fn handle(&self) -> AppHandle {
+        AppHandle {
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+
+    /// Runs the event loop until the last window is closed or [`quit()`] is called.
+    ///
+    /// An `Err` returned by a handler of a window only closes that window, see
+    /// [`on_window_error()`]. An `Err` returned by a closure dispatched with an [`AppHandle`]
+    /// closes all windows and is returned.
+    ///
+    /// [`quit()`]: #method.quit
+    /// [`on_window_error()`]: #method.on_window_error
+    /// [`AppHandle`]: struct.AppHandle.html
+    pub fn run(mut self) -> WVResult {
+        loop {
+            self.run_commands()?;
+            if self.quit || self.windows.is_empty() {
+                return match self.window_error.take() {
+                    Some(e) => Err(e),
+                    None => Ok(()),
+                };
+            }
+
+            // All windows share the native event loop, so blocking on the first one wakes up for
+            // events of any of them. The others only need to check whether they were closed.
+            let mut index = 0;
+            while index < self.windows.len() {
+                match self.windows[index].1.step(index == 0) {
+                    Some(Ok(())) => index += 1,
+                    Some(Err(e)) => {
+                        let id = self.windows[index].0;
+                        self.remove(index);
+                        match self.window_error_handler {
+                            Some(ref mut handler) => handler(id, e),
+                            None => {
+                                if self.window_error.is_none() {
+                                    self.window_error = Some(e);
+                                }
+                            }
+                        }
+                    }
+                    None => self.remove(index),
+                }
+            }
+        }
+    }
+
+    fn run_commands(&mut self) -> WVResult {
+        loop {
+            let commands = mem::take(&mut self.shared.lock().unwrap().commands);
+            if commands.is_empty() {
+                return Ok(());
+            }
+            for command in commands {
+                command(self)?;
+            }
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        let (id, window) = self.windows.remove(index);
+        self.shared
+            .lock()
+            .unwrap()
+            .wakers
+            .retain(|waker| waker.id != id);
+        drop(window);
+    }
+}
+
+/// A thread-safe handle to an [`App`], used to dispatch closures onto its event loop.
+///
+/// [`App`]: struct.App.html
+#[derive(Clone)]
+pub struct AppHandle {
+    shared: Weak<Mutex<Shared>>,
+}
+
+impl AppHandle {
+    /// Schedules a closure to be run on the [`App`]'s thread, e.g. to open a window.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Dispatch`] if the [`App`] has been dropped.
+    ///
+    /// If the closure returns an `Err`, [`App::run()`] returns it.
+    ///
+    /// [`App`]: struct.App.html
+    /// [`App::run()`]: struct.App.html#method.run
+    /// [`Error::Dispatch`]: enum.Error.html#variant.Dispatch
+    pub fn dispatch<F>(&self, f: F) -> WVResult
+    where
+        F: for<'b> FnOnce(&mut App<'b>) -> WVResult + Send + 'static,
+    {
+        let shared = self.shared.upgrade().ok_or(Error::Dispatch)?;
+        let mut shared = shared.lock().map_err(|_| Error::Dispatch)?;
+        shared.commands.push(Box::new(f));
+
+        // Wake up the event loop, which may be blocked waiting for events of any window.
+        for waker in &shared.wakers {
+            if let Some(live) = waker.live.upgrade() {
+                let live = live.read().map_err(|_| Error::Dispatch)?;
+                if *live {
+                    unsafe {
+                        webview_dispatch(waker.inner, Some(ffi_wake_handler), ptr::null_mut())
+                    };
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Schedules closing a window.
+    pub fn close(&self, id: WindowId) -> WVResult {
+        self.dispatch(move |app| {
+            app.close(id);
+            Ok(())
+        })
+    }
+
+    /// Schedules closing all windows, ending [`App::run()`].
+    ///
+    /// [`App::run()`]: struct.App.html#method.run
+    pub fn quit(&self) -> WVResult {
+        self.dispatch(|app| {
+            app.quit();
+            Ok(())
+        })
+    }
+}
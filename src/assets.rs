@@ -0,0 +1,133 @@
+use scheme::{SchemeRequest, SchemeResponse};
+use std::borrow::Cow;
+
+/// The scheme `Content::Assets` is served under.
+pub(crate) const ASSETS_SCHEME: &str = "webview-assets";
+
+/// The URL of the page loaded for `Content::Assets`.
+pub(crate) const ASSETS_URL: &str = "webview-assets://localhost/index.html";
+
+/// A file embedded with [`include_assets!`].
+///
+/// [`include_assets!`]: macro.include_assets.html
+#[derive(Debug, Copy, Clone)]
+pub struct Asset {
+    /// The path relative to the embedded directory, with `/` as separator.
+    pub path: &'static str,
+    pub data: &'static [u8],
+    /// Whether `data` is deflated.
+    pub compressed: bool,
+}
+
+/// A directory embedded into the binary, usually with [`include_assets!`].
+///
+/// Loaded with `Content::Assets`, the files are served from an internal origin, starting with
+/// `index.html`. Relative URLs, ES modules, `fetch` and WebAssembly work like they would when
+/// served over HTTP, without running a local server.
+///
+/// Only supported on GTK. The other backends can't serve custom schemes yet, so
+/// [`WebViewBuilder::build()`] fails with [`Error::SchemeRegistration`] there.
+///
+/// [`include_assets!`]: macro.include_assets.html
+/// [`WebViewBuilder::build()`]: struct.WebViewBuilder.html#method.build
+/// [`Error::SchemeRegistration`]: enum.Error.html#variant.SchemeRegistration
+#[derive(Debug, Copy, Clone)]
+pub struct Assets {
+    assets: &'static [Asset],
+}
+
+impl Assets {
+    pub fn new(assets: &'static [Asset]) -> Assets {
+        Assets { assets }
+    }
+
+    /// Returns the contents of the file at `path`, decompressing it if necessary.
+    pub fn get(&self, path: &str) -> Option<Cow<'static, [u8]>> {
+        let path = path.trim_start_matches('/');
+        let asset = self.assets.iter().find(|asset| asset.path == path)?;
+        if asset.compressed {
+            inflate(asset.data).map(Cow::Owned)
+        } else {
+            Some(Cow::Borrowed(asset.data))
+        }
+    }
+
+    pub(crate) fn respond(&self, request: &SchemeRequest) -> SchemeResponse {
+        let mut path = request.path.clone();
+        if path.ends_with('/') {
+            path.push_str("index.html");
+        }
+        match self.get(&path) {
+            Some(data) => SchemeResponse::new(mime_type(&path), data.into_owned()),
+            None => SchemeResponse::not_found(),
+        }
+    }
+}
+
+#[cfg(feature = "compress-assets")]
+fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    miniz_oxide::inflate::decompress_to_vec(data).ok()
+}
+
+#[cfg(not(feature = "compress-assets"))]
+fn inflate(_data: &[u8]) -> Option<Vec<u8>> {
+    // `include_assets!` only compresses with the feature enabled.
+    None
+}
+
+/// Guesses the MIME type of a file from its extension.
+pub(crate) fn mime_type(path: &str) -> &'static str {
+    let extension = match path.rfind('.') {
+        Some(dot) if !path[dot..].contains('/') => path[dot + 1..].to_ascii_lowercase(),
+        _ => return "application/octet-stream",
+    };
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" | "map" => "application/json",
+        "wasm" => "application/wasm",
+        "txt" => "text/plain",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+#[test]
+fn test_mime_type() {
+    assert_eq!(mime_type("/index.html"), "text/html");
+    assert_eq!(mime_type("/pkg/app_bg.WASM"), "application/wasm");
+    assert_eq!(mime_type("/js/module.mjs"), "text/javascript");
+    assert_eq!(mime_type("/v1.2/LICENSE"), "application/octet-stream");
+}
+
+#[test]
+fn test_get() {
+    static ASSETS: &[Asset] = &[Asset {
+        path: "css/main.css",
+        data: b"body {}",
+        compressed: false,
+    }];
+    let assets = Assets::new(ASSETS);
+    assert_eq!(
+        assets.get("/css/main.css").as_deref(),
+        Some(&b"body {}"[..])
+    );
+    assert!(assets.get("/index.html").is_none());
+}
@@ -0,0 +1,111 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+use {Error, WVResult};
+
+struct Shared<R> {
+    result: Option<WVResult<R>>,
+    waker: Option<Waker>,
+}
+
+/// Creates the two ends passing the result of a closure dispatched with
+/// `Handle::dispatch_async()` back to the awaiting task.
+pub(crate) fn channel<R>() -> (Sender<R>, DispatchResult<R>) {
+    let shared = Arc::new(Mutex::new(Shared {
+        result: None,
+        waker: None,
+    }));
+    (
+        Sender {
+            shared: shared.clone(),
+            sent: false,
+        },
+        DispatchResult { shared },
+    )
+}
+
+pub(crate) struct Sender<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+    sent: bool,
+}
+
+impl<R> Sender<R> {
+    pub(crate) fn send(mut self, result: WVResult<R>) {
+        self.complete(result);
+    }
+
+    fn complete(&mut self, result: WVResult<R>) {
+        self.sent = true;
+        let waker = {
+            let mut shared = self.shared.lock().unwrap();
+            shared.result = Some(result);
+            shared.waker.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl<R> Drop for Sender<R> {
+    fn drop(&mut self) {
+        // The closure was dropped without being run, e.g. because the WebView was dropped first.
+        if !self.sent {
+            self.complete(Err(Error::Dispatch));
+        }
+    }
+}
+
+/// Resolves to the result of a closure dispatched with `Handle::dispatch_async()`.
+pub(crate) struct DispatchResult<R> {
+    shared: Arc<Mutex<Shared<R>>>,
+}
+
+impl<R> Future for DispatchResult<R> {
+    type Output = WVResult<R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<WVResult<R>> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[test]
+fn test_channel() {
+    use std::task::{RawWaker, RawWakerVTable};
+
+    fn noop_raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            noop_raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+
+    let (sender, mut result) = channel();
+    assert!(Pin::new(&mut result).poll(&mut cx).is_pending());
+    sender.send(Ok(42));
+    match Pin::new(&mut result).poll(&mut cx) {
+        Poll::Ready(Ok(42)) => {}
+        _ => panic!("expected the sent value"),
+    }
+
+    let (sender, mut result) = channel::<()>();
+    drop(sender);
+    match Pin::new(&mut result).poll(&mut cx) {
+        Poll::Ready(Err(Error::Dispatch)) => {}
+        _ => panic!("expected Error::Dispatch"),
+    }
+}
@@ -1,7 +1,10 @@
+use serde_json;
 use std::{
     error,
     ffi::NulError,
     fmt::{self, Debug, Display},
+    io,
+    path::PathBuf,
 };
 
 pub trait CustomError: Display + Debug + Send + Sync + 'static {}
@@ -19,8 +22,29 @@ pub enum Error {
     NulByte(NulError),
     /// An error occurred while evaluating JavaScript in a WebView instance.
     JsEvaluation,
+    /// The evaluated JavaScript threw an exception. Contains the exception message.
+    JsException(String),
+    /// A value passed between Rust and JavaScript could not be converted to or from JSON.
+    Json(serde_json::Error),
     /// An error occurred while injecting CSS into a WebView instance.
     CssInjection,
+    /// A custom URI scheme could not be registered, because the backend doesn't support them.
+    SchemeRegistration,
+    /// A timer could not be created, because the backend doesn't support them.
+    Timer,
+    /// A window icon could not be decoded, or doesn't have as many pixels as its size implies.
+    Icon,
+    /// The backend doesn't support the requested feature. Contains a description of it.
+    Unsupported(&'static str),
+    /// A file could not be read, e.g. by [`Icon::from_file()`].
+    ///
+    /// [`Icon::from_file()`]: struct.Icon.html#method.from_file
+    Io(io::Error),
+    /// The file or directory given for `Content::File` doesn't exist. Contains its path.
+    FileNotFound(PathBuf),
+    /// The file or directory given for `Content::File` has a path that isn't valid Unicode, so it
+    /// can't be turned into a URL. Contains its path.
+    NonUnicodePath(PathBuf),
     /// Failure to dispatch a closure to a WebView instance via a handle, likely because the
     /// WebView was dropped.
     Dispatch,
@@ -39,6 +63,8 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Error::NulByte(ref cause) => Some(cause),
+            Error::Json(ref cause) => Some(cause),
+            Error::Io(ref cause) => Some(cause),
             _ => None,
         }
     }
@@ -53,7 +79,20 @@ impl Display for Error {
             Error::Initialization => write!(f, "Webview failed to initialize."),
             Error::NulByte(cause) => write!(f, "{}", cause),
             Error::JsEvaluation => write!(f, "Failed to evaluate JavaScript."),
+            Error::JsException(message) => write!(f, "JavaScript exception: {}", message),
+            Error::Json(cause) => write!(f, "{}", cause),
             Error::CssInjection => write!(f, "Failed to inject CSS."),
+            Error::SchemeRegistration => write!(f, "Failed to register a custom URI scheme."),
+            Error::Timer => write!(f, "Failed to create a timer."),
+            Error::Icon => write!(f, "Failed to set the window icon."),
+            Error::Unsupported(feature) => {
+                write!(f, "{} is not supported on this platform.", feature)
+            }
+            Error::Io(cause) => write!(f, "{}", cause),
+            Error::FileNotFound(path) => write!(f, "File not found: {}.", path.display()),
+            Error::NonUnicodePath(path) => {
+                write!(f, "Path is not valid Unicode: {}.", path.display())
+            }
             Error::Dispatch => write!(
                 f,
                 "Closure could not be dispatched. WebView was likely dropped."
@@ -71,3 +110,15 @@ impl From<NulError> for Error {
         Error::NulByte(e)
     }
 }
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Error {
+        Error::Json(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
@@ -1,3 +1,5 @@
+use serde::Serialize;
+use serde_json;
 use std::fmt::{self, Write};
 
 /// Escape a string to pass it into JavaScript.
@@ -73,9 +75,27 @@ impl<'a> fmt::Display for Escaper<'a> {
     }
 }
 
+/// Serializes a value to JSON that can be embedded in JavaScript code.
+///
+/// JSON is almost a subset of JavaScript, except that U+2028 and U+2029 may appear literally in
+/// JSON strings, but not in JavaScript string literals before ES2019.
+pub(crate) fn escape_json<S: Serialize>(value: &S) -> serde_json::Result<String> {
+    let json = serde_json::to_string(value)?;
+    Ok(json
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029"))
+}
+
 #[test]
 fn test() {
     let plain = "ABC \n\r' abc \\  \u{2028}   \u{2029}123";
     let escaped = escape(plain).to_string();
     assert!(escaped == "'ABC \\n\\r\\' abc \\\\  \\u2028   \\u2029123'");
 }
+
+#[test]
+fn test_json() {
+    let value = ("ABC \n\"\u{2028}\u{2029}", 123);
+    let escaped = escape_json(&value).unwrap();
+    assert!(escaped == "[\"ABC \\n\\\"\\u2028\\u2029\",123]");
+}
@@ -0,0 +1,66 @@
+use escape::escape_json;
+use serde::Serialize;
+use WVResult;
+
+/// JavaScript defining `window.webview.listen`, through which the page subscribes to events
+/// emitted with [`WebView::emit()`].
+///
+/// A `webviewready` event is dispatched on `window` once both the bridge and the document are
+/// ready, so scripts also work on backends where the bridge is only installed after loading.
+///
+/// [`WebView::emit()`]: struct.WebView.html#method.emit
+pub(crate) const EVENT_BRIDGE: &str = r#"
+(function() {
+    if (window.webview) {
+        return;
+    }
+    var listeners = {};
+    window.webview = {
+        listen: function(event, callback) {
+            (listeners[event] = listeners[event] || []).push(callback);
+            return function() {
+                var callbacks = listeners[event] || [];
+                var index = callbacks.indexOf(callback);
+                if (index >= 0) {
+                    callbacks.splice(index, 1);
+                }
+            };
+        },
+        _emit: function(event, payload) {
+            var callbacks = (listeners[event] || []).slice();
+            for (var i = 0; i < callbacks.length; i++) {
+                try {
+                    callbacks[i](payload);
+                } catch (e) {
+                    setTimeout(function() { throw e; });
+                }
+            }
+        }
+    };
+    var ready = function() {
+        var event = document.createEvent('Event');
+        event.initEvent('webviewready', false, false);
+        window.dispatchEvent(event);
+    };
+    if (document.readyState === 'loading') {
+        document.addEventListener('DOMContentLoaded', ready);
+    } else {
+        ready();
+    }
+})();
+"#;
+
+/// Returns JavaScript delivering `payload` to the listeners of `event`.
+pub(crate) fn emit_js<S: Serialize>(event: &str, payload: &S) -> WVResult<String> {
+    Ok(format!(
+        "window.webview && window.webview._emit({}, {})",
+        escape_json(&event)?,
+        escape_json(payload)?
+    ))
+}
+
+#[test]
+fn test_emit_js() {
+    let js = emit_js("tick", &(1, "two")).unwrap();
+    assert!(js == r#"window.webview && window.webview._emit("tick", [1,"two"])"#);
+}
@@ -0,0 +1,52 @@
+use std::path::{Component, Path, Prefix};
+
+/// Percent-encodes what a URL parser would in a path, and `%` itself, so the URL stays the same
+/// once the backend parses it and can be compared with the URLs of the page's requests.
+fn encode(component: &str) -> String {
+    let mut encoded = String::new();
+    for &byte in component.as_bytes() {
+        if byte <= b' ' || byte >= 0x7f || b"\"#%<>?\\`{}".contains(&byte) {
+            encoded.push_str(&format!("%{:02X}", byte));
+        } else {
+            encoded.push(byte as char);
+        }
+    }
+    encoded
+}
+
+/// Turns an absolute path into a `file:` URL, percent-encoding each component.
+pub(crate) fn file_url(path: &str) -> String {
+    let path = Path::new(path);
+    let mut url = String::from("file://");
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => match prefix.kind() {
+                Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                    url.push('/');
+                    url.push(letter as char);
+                    url.push(':');
+                }
+                _ => url.push_str(&prefix.as_os_str().to_string_lossy()),
+            },
+            Component::RootDir => {}
+            component => {
+                url.push('/');
+                url.push_str(&encode(&component.as_os_str().to_string_lossy()));
+            }
+        }
+    }
+    url
+}
+
+#[cfg(unix)]
+#[test]
+fn test_file_url() {
+    assert_eq!(
+        file_url("/home/me/my app/index.html"),
+        "file:///home/me/my%20app/index.html"
+    );
+    assert_eq!(
+        file_url("/home/me/50% (final)/ü.html"),
+        "file:///home/me/50%25%20(final)/%C3%BC.html"
+    );
+}
@@ -0,0 +1,75 @@
+use std::{fs, path::Path};
+use {Error, WVResult};
+
+/// An icon of the window, shown e.g. in the taskbar and when switching windows.
+///
+/// Set with [`WebViewBuilder::icon()`] or [`WebView::set_icon()`].
+///
+/// [`WebViewBuilder::icon()`]: struct.WebViewBuilder.html#method.icon
+/// [`WebView::set_icon()`]: struct.WebView.html#method.set_icon
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icon {
+    pub(crate) data: Vec<u8>,
+    // `None` for the contents of a file, which are decoded by the backend.
+    pub(crate) size: Option<(u32, u32)>,
+}
+
+impl Icon {
+    /// Creates an icon from the contents of a PNG or ICO file, e.g. embedded with
+    /// `include_bytes!()`. They are only decoded when the icon is set.
+    pub fn from_bytes<B: Into<Vec<u8>>>(bytes: B) -> Icon {
+        Icon {
+            data: bytes.into(),
+            size: None,
+        }
+    }
+
+    /// Reads an icon from a PNG or ICO file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] if the file can't be read.
+    ///
+    /// [`Error::Io`]: enum.Error.html#variant.Io
+    pub fn from_file<P: AsRef<Path>>(path: P) -> WVResult<Icon> {
+        Ok(Icon::from_bytes(fs::read(path)?))
+    }
+
+    /// Creates an icon from `width * height` pixels of four bytes each, red, green, blue and
+    /// alpha, row by row from the top.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Icon`] if `rgba` has another length.
+    ///
+    /// [`Error::Icon`]: enum.Error.html#variant.Icon
+    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> WVResult<Icon> {
+        if width == 0
+            || height == 0
+            || width > i32::MAX as u32
+            || height > i32::MAX as u32
+            || rgba.len() as u64 != u64::from(width) * u64::from(height) * 4
+        {
+            return Err(Error::Icon);
+        }
+        Ok(Icon {
+            data: rgba,
+            size: Some((width, height)),
+        })
+    }
+}
+
+#[test]
+fn test_icon_from_rgba() {
+    assert!(Icon::from_rgba(vec![0; 16 * 16 * 4], 16, 16).is_ok());
+    assert!(Icon::from_rgba(vec![0; 16 * 16 * 3], 16, 16).is_err());
+    assert!(Icon::from_rgba(Vec::new(), 0, 0).is_err());
+}
+
+#[test]
+fn test_icon_from_file() {
+    match Icon::from_file("does/not/exist.png") {
+        Err(Error::Io(ref error)) if error.kind() == std::io::ErrorKind::NotFound => {}
+        _ => panic!("expected Error::Io"),
+    }
+}
@@ -24,30 +24,85 @@
 #![allow(deprecated)] // TODO: remove this when removing dialogs
 
 extern crate boxfnonce;
+#[cfg(feature = "compress-assets")]
+extern crate miniz_oxide;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
 extern crate tinyfiledialogs as tfd;
 extern crate urlencoding;
+extern crate webview_macros;
 extern crate webview_sys as ffi;
 
+mod app;
+mod assets;
 mod color;
 mod dialog;
+mod dispatch;
 mod error;
 mod escape;
-
+mod event;
+mod file;
+mod icon;
+mod navigation;
+mod persist;
+mod rpc;
+mod scheme;
+mod script;
+mod style;
+mod task;
+mod timer;
+mod window;
+
+pub use app::{App, AppHandle, WindowErrorHandler, WindowId};
+pub use assets::{Asset, Assets};
 pub use color::Color;
 pub use dialog::DialogBuilder;
 pub use error::{CustomError, Error, WVResult};
 pub use escape::escape;
+pub use icon::Icon;
+pub use navigation::{
+    Modifiers, NavigationHandler, NavigationRequest, NavigationType, NewWindowHandler,
+    NewWindowPolicy, NewWindowRequest, Policy, WindowConfig, WindowFeatures,
+};
+pub use rpc::{Responder, RpcHandler};
+pub use scheme::{SchemeBody, SchemeHandler, SchemeRequest, SchemeResponse};
+pub use script::{Frames, InjectAt, UserScript};
+pub use style::{StyleLevel, StyleSheet};
+pub use task::LocalHandle;
+pub use timer::TimerId;
+pub use webview_macros::include_assets;
+pub use window::{CloseRequestedHandler, WindowEvent, WindowEventHandler, WindowType};
 
 use boxfnonce::SendBoxFnOnce;
 use ffi::*;
+use persist::{Persist, WindowState};
+use rpc::Message;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
 use std::{
+    collections::HashMap,
     ffi::{CStr, CString},
+    future::Future,
     marker::PhantomData,
     mem,
     os::raw::*,
-    sync::{Arc, RwLock, Weak},
+    path::PathBuf,
+    ptr,
+    sync::{
+        atomic::AtomicBool,
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, RwLock, Weak,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
 };
-use urlencoding::encode;
+use task::{LocalFuture, TaskWaker};
+use timer::Timer;
+
+/// The base URL of HTML set without one.
+const ABOUT_BLANK: &[u8] = b"about:blank\0";
 
 /// JavaScript function used to insert new css rules to webview.
 /// This function should be called with only one argument
@@ -65,13 +120,26 @@ const CSS_INJECT_FUNCTION: &str = "(function(e){var \
 /// # Variants
 ///
 /// - `Url` - Content to be fetched from a URL.
-/// - `Html` - A string containing literal HTML.
+/// - `Html` - A string containing literal HTML. Relative URLs in it are resolved against
+///   [`WebViewBuilder::base_url()`].
+/// - `Assets` - A directory embedded with [`include_assets!`], starting with its `index.html`.
+///   Only supported on GTK, [`WebViewBuilder::build()`] fails with [`Error::SchemeRegistration`]
+///   on the other backends.
+/// - `File` - A local HTML file. The page can read the files in its directory, or the one set
+///   with [`WebViewBuilder::allowed_read_dir()`].
 ///
 /// [`WebView`]: struct.WebView.html
+/// [`include_assets!`]: macro.include_assets.html
+/// [`WebViewBuilder::allowed_read_dir()`]: struct.WebViewBuilder.html#method.allowed_read_dir
+/// [`WebViewBuilder::base_url()`]: struct.WebViewBuilder.html#method.base_url
+/// [`WebViewBuilder::build()`]: struct.WebViewBuilder.html#method.build
+/// [`Error::SchemeRegistration`]: enum.Error.html#variant.SchemeRegistration
 #[derive(Debug)]
 pub enum Content<T> {
     Url(T),
     Html(T),
+    Assets(Assets),
+    File(PathBuf),
 }
 
 /// Builder for constructing a [`WebView`] instance.
@@ -110,10 +178,34 @@ pub struct WebViewBuilder<'a, T: 'a, I, C> {
     pub invoke_handler: Option<I>,
     pub user_data: Option<T>,
     pub frameless: bool,
+    pub transparent: bool,
     pub visible: bool,
     pub min_width: i32,
     pub min_height: i32,
+    pub max_size: Option<(i32, i32)>,
+    pub aspect_ratio: Option<f64>,
+    pub position: Option<(i32, i32)>,
+    pub persist_window_state: Option<(&'a str, PathBuf)>,
+    pub icon: Option<Icon>,
+    pub always_on_top: bool,
+    pub skip_taskbar: bool,
+    pub skip_pager: bool,
+    pub sticky: bool,
+    pub window_type: WindowType,
     pub hide_instead_of_close: bool,
+    pub rpc_handlers: HashMap<String, RpcHandler<'a, T>>,
+    pub user_scripts: Vec<UserScript<'a>>,
+    pub navigation_handler: Option<NavigationHandler<'a>>,
+    pub new_window_handler: Option<NewWindowHandler<'a>>,
+    pub on_load_started: Option<LoadHandler<'a, T>>,
+    pub on_load_committed: Option<LoadHandler<'a, T>>,
+    pub on_load_finished: Option<LoadHandler<'a, T>>,
+    pub on_load_failed: Option<LoadFailedHandler<'a, T>>,
+    pub on_window_event: Option<WindowEventHandler<'a, T>>,
+    pub on_close_requested: Option<CloseRequestedHandler<'a, T>>,
+    pub scheme_handlers: HashMap<String, SchemeHandler<'a>>,
+    pub allowed_read_dir: Option<PathBuf>,
+    pub base_url: Option<&'a str>,
 }
 
 impl<'a, T: 'a, I, C> Default for WebViewBuilder<'a, T, I, C>
@@ -137,10 +229,34 @@ where
             invoke_handler: None,
             user_data: None,
             frameless: false,
+            transparent: false,
             visible: true,
             min_width: 300,
             min_height: 300,
+            max_size: None,
+            aspect_ratio: None,
+            position: None,
+            persist_window_state: None,
+            icon: None,
+            always_on_top: false,
+            skip_taskbar: false,
+            skip_pager: false,
+            sticky: false,
+            window_type: WindowType::Normal,
             hide_instead_of_close: false,
+            rpc_handlers: HashMap::new(),
+            user_scripts: Vec::new(),
+            navigation_handler: None,
+            new_window_handler: None,
+            on_load_started: None,
+            on_load_committed: None,
+            on_load_finished: None,
+            on_load_failed: None,
+            on_window_event: None,
+            on_close_requested: None,
+            scheme_handlers: HashMap::new(),
+            allowed_read_dir: None,
+            base_url: None,
         }
     }
 }
@@ -171,6 +287,32 @@ where
         self
     }
 
+    /// Sets the directory a page loaded with `Content::File` can read files from, e.g. to give
+    /// `dist/index.html` access to `assets/`.
+    ///
+    /// Defaults to the directory containing the file. Only enforced on GTK and macOS, where the
+    /// page can't load any other local file, be it by navigating, as an image or script, or with
+    /// `fetch`. On GTK, only navigations are restricted without the `webkit2gtk-2-24` feature,
+    /// and `fetch` can't read any local file. On Windows, access to local files depends on the
+    /// security zone settings.
+    pub fn allowed_read_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.allowed_read_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets the URL relative links and resources of `Content::Html` are resolved against, which
+    /// is also the origin of the page, e.g. `https://example.com/app/` or a scheme registered
+    /// with [`register_scheme()`]. Also used by [`WebView::set_html()`].
+    ///
+    /// Ignored on Windows, where pages set with HTML have no base URL.
+    ///
+    /// [`register_scheme()`]: #method.register_scheme
+    /// [`WebView::set_html()`]: struct.WebView.html#method.set_html
+    pub fn base_url(mut self, base_url: &'a str) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
     /// Sets the size of the WebView window.
     ///
     /// Defaults to 800 x 600.
@@ -203,6 +345,20 @@ where
         self
     }
 
+    /// Makes the background of the WebView window transparent, so the desktop shows through
+    /// wherever the page doesn't paint, e.g. with `background: transparent` on its `<html>` and
+    /// `<body>`. Combined with [`frameless()`], this allows widgets of any shape.
+    ///
+    /// Needs a compositing window manager on Linux. Not supported on Windows.
+    ///
+    /// defaults to `false`
+    ///
+    /// [`frameless()`]: #method.frameless
+    pub fn transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+
     /// Set the visibility of the WebView window.
     ///
     /// defaults to `true`
@@ -220,6 +376,98 @@ where
         self
     }
 
+    /// Sets the maximum size of the WebView window. A negative width or height leaves that
+    /// dimension unlimited.
+    ///
+    /// Defaults to no maximum.
+    pub fn max_size(mut self, width: i32, height: i32) -> Self {
+        self.max_size = Some((width, height));
+        self
+    }
+
+    /// Keeps the width of the WebView window divided by its height at `ratio` when the user
+    /// resizes it. Not enforced on Windows.
+    pub fn aspect_ratio(mut self, ratio: f64) -> Self {
+        self.aspect_ratio = Some(ratio);
+        self
+    }
+
+    /// Sets the position of the top left corner of the WebView window on the screen. Ignored on
+    /// macOS.
+    ///
+    /// Defaults to centering the window.
+    pub fn position(mut self, x: i32, y: i32) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// Saves the size, position, maximized and fullscreen state and zoom level of the WebView
+    /// window to the JSON file at `path` a second after they change and when it is closed, and
+    /// restores them the next time it is built. Several windows can share a file as long as their
+    /// `key`s differ.
+    ///
+    /// A restored window is moved onto the available monitors if it would be off-screen. The
+    /// [`size()`] and [`position()`] set on the builder only apply when nothing was saved yet.
+    ///
+    /// Changes are only noticed on GTK so far, elsewhere the state stays as it was restored.
+    /// Zooming with the keyboard or mouse is noticed when the state is saved.
+    ///
+    /// [`size()`]: #method.size
+    /// [`position()`]: #method.position
+    pub fn persist_window_state<P: Into<PathBuf>>(mut self, key: &'a str, path: P) -> Self {
+        self.persist_window_state = Some((key, path.into()));
+        self
+    }
+
+    /// Sets the icon of the WebView window. Ignored on macOS, where windows show the icon of the
+    /// app bundle instead.
+    ///
+    /// Defaults to the icon of the desktop environment or the executable.
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Keeps the WebView window above other windows.
+    ///
+    /// Defaults to `false`.
+    pub fn always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// Hides the WebView window from the taskbar. Only supported on Linux so far.
+    ///
+    /// Defaults to `false`.
+    pub fn skip_taskbar(mut self, skip: bool) -> Self {
+        self.skip_taskbar = skip;
+        self
+    }
+
+    /// Hides the WebView window from the workspace switcher. Only supported on Linux.
+    ///
+    /// Defaults to `false`.
+    pub fn skip_pager(mut self, skip: bool) -> Self {
+        self.skip_pager = skip;
+        self
+    }
+
+    /// Shows the WebView window on all workspaces. Not supported on Windows yet.
+    ///
+    /// Defaults to `false`.
+    pub fn sticky(mut self, sticky: bool) -> Self {
+        self.sticky = sticky;
+        self
+    }
+
+    /// Tells the window manager what the WebView window is used for.
+    ///
+    /// Defaults to `WindowType::Normal`.
+    pub fn window_type(mut self, window_type: WindowType) -> Self {
+        self.window_type = window_type;
+        self
+    }
+
     /// Sets behavior of the window when closed.
     ///
     /// default to `false`
@@ -231,16 +479,302 @@ where
     /// Sets the invoke handler callback. This will be called when a message is received from
     /// JavaScript.
     ///
+    /// Required unless handlers for `rpc.call` are registered with [`rpc()`] or
+    /// [`rpc_async()`].
+    ///
     /// # Errors
     ///
     /// If the closure returns an `Err`, it will be returned on the next call to [`step()`].
     ///
+    /// [`rpc()`]: #method.rpc
+    /// [`rpc_async()`]: #method.rpc_async
     /// [`step()`]: struct.WebView.html#method.step
     pub fn invoke_handler(mut self, invoke_handler: I) -> Self {
         self.invoke_handler = Some(invoke_handler);
         self
     }
 
+    /// Registers a handler for calls made from JavaScript with `rpc.call(name, args)`.
+    ///
+    /// `args` is deserialized into `A` and the value returned by the handler resolves the promise
+    /// returned by `rpc.call`. If `args` cannot be deserialized or the handler returns an `Err`,
+    /// the promise is rejected with the error message instead.
+    ///
+    /// The `rpc` object is defined before the page's own scripts run. It relies on `Promise`,
+    /// which is not supported by MSHTML.
+    ///
+    /// Once an RPC handler is registered, [`invoke_handler()`] is optional. The type of the
+    /// missing handler still has to be named then, which [`NoInvokeHandler`] is for.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// web_view::builder::<_, NoInvokeHandler<_>, _>()
+    ///     .rpc("add", |_webview, (a, b): (i32, i32)| Ok(a + b))
+    /// ```
+    ///
+    /// ```javascript
+    /// const sum = await rpc.call('add', [1, 2]);
+    /// ```
+    ///
+    /// [`invoke_handler()`]: #method.invoke_handler
+    /// [`NoInvokeHandler`]: type.NoInvokeHandler.html
+    pub fn rpc<A, R, F>(mut self, name: &str, handler: F) -> Self
+    where
+        A: DeserializeOwned,
+        R: Serialize,
+        F: FnMut(&mut WebView<T>, A) -> WVResult<R> + 'a,
+    {
+        self.rpc_handlers
+            .insert(name.to_string(), rpc::rpc_handler(handler));
+        self
+    }
+
+    /// Registers a handler for calls made from JavaScript with `rpc.call(name, args)` which
+    /// completes later.
+    ///
+    /// Like [`rpc()`], but instead of returning a value the handler receives a [`Responder`]
+    /// which settles the promise returned by `rpc.call`. The responder can be moved to another
+    /// thread, so slow work doesn't block the event loop. Calls still pending when the page
+    /// navigates away are cancelled, see [`Responder`] for platform support.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// builder.rpc_async("fetch", |_webview, url: String, responder| {
+    ///     thread::spawn(move || {
+    ///         responder.respond(download(&url)).unwrap();
+    ///     });
+    /// })
+    /// ```
+    ///
+    /// [`rpc()`]: struct.WebViewBuilder.html#method.rpc
+    /// [`Responder`]: struct.Responder.html
+    pub fn rpc_async<A, R, F>(mut self, name: &str, handler: F) -> Self
+    where
+        A: DeserializeOwned,
+        R: Serialize,
+        F: FnMut(&mut WebView<T>, A, Responder<R>) + 'a,
+    {
+        self.rpc_handlers
+            .insert(name.to_string(), rpc::rpc_async_handler(handler));
+        self
+    }
+
+    /// Adds a script which runs in every page the `WebView` loads, including after navigating or
+    /// reloading.
+    ///
+    /// Scripts run in the order they were added, after the crate's own bridges such as
+    /// `window.external`, so they can already use those. See [`WebView::add_user_script()`] for
+    /// platform support.
+    ///
+    /// [`WebView::add_user_script()`]: struct.WebView.html#method.add_user_script
+    pub fn user_script(mut self, source: &'a str, inject_at: InjectAt, frames: Frames) -> Self {
+        self.user_scripts.push(UserScript {
+            source,
+            inject_at,
+            frames,
+        });
+        self
+    }
+
+    /// Sets a handler deciding what happens when the page is about to navigate somewhere.
+    ///
+    /// Every navigation is passed to the handler before it starts, including link clicks, form
+    /// submissions and calls to [`WebView::navigate()`]. Returning [`Policy::Deny`] keeps a link
+    /// from taking the app's window, and with it `external.invoke`, to an untrusted site.
+    ///
+    /// Only supported on GTK, other platforms allow every navigation.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// builder.navigation_handler(|request| {
+    ///     if request.url == "about:blank" {
+    ///         Policy::Allow
+    ///     } else {
+    ///         Policy::OpenInSystemBrowser
+    ///     }
+    /// })
+    /// ```
+    ///
+    /// [`WebView::navigate()`]: struct.WebView.html#method.navigate
+    /// [`Policy::Deny`]: enum.Policy.html#variant.Deny
+    pub fn navigation_handler<F>(mut self, navigation_handler: F) -> Self
+    where
+        F: FnMut(&NavigationRequest) -> Policy + 'a,
+    {
+        self.navigation_handler = Some(Box::new(navigation_handler));
+        self
+    }
+
+    /// Sets a handler deciding what happens when the page opens a window with `window.open` or a
+    /// link with `target="_blank"`.
+    ///
+    /// Windows the handler lets open share the handlers, user data and bridges of this one, and
+    /// are closed along with it. Without a handler, every such window is opened with
+    /// [`NewWindowRequest::config()`].
+    ///
+    /// Only supported on GTK.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// builder.new_window_handler(|request| {
+    ///     if request.url.starts_with("https://") {
+    ///         NewWindowPolicy::OpenInSystemBrowser
+    ///     } else {
+    ///         NewWindowPolicy::Open(WindowConfig {
+    ///             title: "Preview".to_string(),
+    ///             ..request.config()
+    ///         })
+    ///     }
+    /// })
+    /// ```
+    ///
+    /// [`NewWindowRequest::config()`]: struct.NewWindowRequest.html#method.config
+    pub fn new_window_handler<F>(mut self, new_window_handler: F) -> Self
+    where
+        F: FnMut(&NewWindowRequest) -> NewWindowPolicy + 'a,
+    {
+        self.new_window_handler = Some(Box::new(new_window_handler));
+        self
+    }
+
+    /// Serves requests for URIs with the given scheme, e.g. `app://localhost/index.html`, from
+    /// Rust.
+    ///
+    /// This lets the app load its frontend without running a local HTTP server. Pages with a
+    /// registered scheme are treated as secure, and `fetch` can be used with them. Registering a
+    /// scheme again replaces its handler. Each WebView answers the requests of its own pages, so
+    /// several WebViews can register the same scheme with different handlers.
+    ///
+    /// Only supported on GTK, [`build()`] fails with [`Error::SchemeRegistration`] elsewhere.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// builder
+    ///     .content(Content::Url("app://localhost/index.html"))
+    ///     .register_scheme("app", |request| match request.path.as_str() {
+    ///         "/index.html" => SchemeResponse::new("text/html", INDEX_HTML),
+    ///         _ => SchemeResponse::not_found(),
+    ///     })
+    /// ```
+    ///
+    /// [`build()`]: #method.build
+    /// [`Error::SchemeRegistration`]: enum.Error.html#variant.SchemeRegistration
+    pub fn register_scheme<F>(mut self, scheme: &str, handler: F) -> Self
+    where
+        F: FnMut(SchemeRequest) -> SchemeResponse + 'a,
+    {
+        self.scheme_handlers
+            .insert(scheme.to_string(), Box::new(handler));
+        self
+    }
+
+    /// Sets a callback for when the `WebView` starts loading a page. Receives the URL of the
+    /// page.
+    ///
+    /// Load events are only reported on GTK. Like with the invoke handler, an `Err` returned by
+    /// any load callback is returned by the next call to [`step()`].
+    ///
+    /// [`step()`]: struct.WebView.html#method.step
+    pub fn on_load_started<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut WebView<T>, &str) -> WVResult + 'a,
+    {
+        self.on_load_started = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets a callback for when the `WebView` has started receiving the page, which has replaced
+    /// the previous one. Receives the URL of the page, which may differ from the one passed to
+    /// [`on_load_started()`] if there were redirects.
+    ///
+    /// [`on_load_started()`]: struct.WebViewBuilder.html#method.on_load_started
+    pub fn on_load_committed<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut WebView<T>, &str) -> WVResult + 'a,
+    {
+        self.on_load_committed = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets a callback for when the `WebView` has finished loading a page, which is a good time
+    /// to [`eval()`] code relying on it. Receives the URL of the page.
+    ///
+    /// Also called after [`on_load_failed()`].
+    ///
+    /// [`eval()`]: struct.WebView.html#method.eval
+    /// [`on_load_failed()`]: struct.WebViewBuilder.html#method.on_load_failed
+    pub fn on_load_finished<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut WebView<T>, &str) -> WVResult + 'a,
+    {
+        self.on_load_finished = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets a callback for when loading a page failed. Receives the URL which failed to load and
+    /// a description of the error.
+    ///
+    /// An error page is shown unless the callback navigates somewhere else, e.g. to retry.
+    pub fn on_load_failed<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut WebView<T>, &str, &str) -> WVResult + 'a,
+    {
+        self.on_load_failed = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets a callback for when the window is resized, moved, focused, minimized, maximized or
+    /// made fullscreen, or leaves one of these states.
+    ///
+    /// Window events are only reported on GTK, and not for windows opened by the page. Like with
+    /// the invoke handler, an `Err` returned by the callback is returned by the next call to
+    /// [`step()`].
+    ///
+    /// [`step()`]: struct.WebView.html#method.step
+    pub fn on_window_event<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut WebView<T>, WindowEvent) -> WVResult + 'a,
+    {
+        self.on_window_event = Some(Box::new(handler));
+        self
+    }
+
+    /// Sets a callback for when the user asks to close the window. Returning `Ok(false)` keeps it
+    /// open, e.g. to ask about unsaved changes first.
+    ///
+    /// Not called when the window is closed with [`WebView::exit()`]. An `Err` returned by the
+    /// callback keeps the window open, and is returned by the next call to [`step()`].
+    ///
+    /// Only supported on GTK. Windows opened by the page close without asking.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// builder.on_close_requested(|webview| {
+    ///     if webview.user_data().unsaved_changes {
+    ///         webview.eval("showSaveDialog()")?;
+    ///         Ok(false)
+    ///     } else {
+    ///         Ok(true)
+    ///     }
+    /// })
+    /// ```
+    ///
+    /// [`WebView::exit()`]: struct.WebView.html#method.exit
+    /// [`step()`]: struct.WebView.html#method.step
+    pub fn on_close_requested<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&mut WebView<T>) -> WVResult<bool> + 'a,
+    {
+        self.on_close_requested = Some(Box::new(handler));
+        self
+    }
+
     /// Sets the initial state of the user data. This is an arbitrary value stored on the WebView
     /// thread, accessible from dispatched closures without synchronization overhead.
     pub fn user_data(mut self, user_data: T) -> Self {
@@ -258,31 +792,214 @@ where
         }
 
         let title = CString::new(self.title)?;
+        let mut scheme_handlers = self.scheme_handlers;
         let content = require_field!(content);
+        let mut file_access = None;
+        let mut html = None;
         let url = match content {
-            Content::Url(url) => CString::new(url.as_ref())?,
-            Content::Html(html) => {
-                CString::new(format!("data:text/html,{}", encode(html.as_ref())))?
+            Content::Url(url) => Some(CString::new(url.as_ref())?),
+            Content::Html(content) => {
+                html = Some(CString::new(content.as_ref())?);
+                None
+            }
+            Content::Assets(assets) => {
+                scheme_handlers.insert(
+                    assets::ASSETS_SCHEME.to_string(),
+                    Box::new(move |request| assets.respond(&request)),
+                );
+                Some(CString::new(assets::ASSETS_URL)?)
+            }
+            Content::File(path) => {
+                let path = path.canonicalize().map_err(|_| Error::FileNotFound(path))?;
+                let dir = match self.allowed_read_dir {
+                    Some(dir) => dir.canonicalize().map_err(|_| Error::FileNotFound(dir))?,
+                    None => path.parent().unwrap_or(&path).to_path_buf(),
+                };
+                let url = match path.to_str() {
+                    Some(path) => CString::new(file::file_url(path))?,
+                    None => return Err(Error::NonUnicodePath(path)),
+                };
+                let dir = match dir.to_str() {
+                    Some(dir) => CString::new(dir)?,
+                    None => return Err(Error::NonUnicodePath(dir)),
+                };
+                file_access = Some((url, dir));
+                None
             }
         };
         let user_data = require_field!(user_data);
-        let invoke_handler = require_field!(invoke_handler);
+        let invoke_handler: Box<dyn FnMut(&mut WebView<T>, &str) -> WVResult + 'a> =
+            match self.invoke_handler {
+                Some(invoke_handler) => Box::new(invoke_handler),
+                // Pages talking to Rust only through `rpc.call` don't need one.
+                None if !self.rpc_handlers.is_empty() => Box::new(|_, _| Ok(())),
+                None => return Err(Error::UninitializedField("invoke_handler")),
+            };
 
-        WebView::new(
+        let base_url = match self.base_url {
+            Some(base_url) => Some(CString::new(base_url)?),
+            None => None,
+        };
+
+        let mut persist = match self.persist_window_state {
+            Some((key, path)) => Some(Persist {
+                webview: ptr::null_mut(),
+                key: key.to_string(),
+                path,
+                state: WindowState {
+                    x: 0,
+                    y: 0,
+                    width: self.width,
+                    height: self.height,
+                    maximized: false,
+                    fullscreen: false,
+                    zoom_level: 1.0,
+                },
+                save_timer: None,
+            }),
+            None => None,
+        };
+        let saved = persist.as_ref().and_then(Persist::load);
+        let (width, height) = saved.map_or((self.width, self.height), |saved| {
+            (saved.width, saved.height)
+        });
+        // Shown once it has been moved, so it doesn't appear centered first. The window type and
+        // transparency are only applied to windows that aren't shown yet.
+        let show_later = self.position.is_some()
+            || saved.is_some()
+            || self.window_type != WindowType::Normal
+            || self.transparent;
+
+        let mut webview = WebView::new(
             &title,
-            &url,
-            self.width,
-            self.height,
+            width,
+            height,
             self.resizable,
             self.debug,
             self.frameless,
-            self.visible,
+            self.visible && !show_later,
             self.min_width,
             self.min_height,
             self.hide_instead_of_close,
             user_data,
             invoke_handler,
-        )
+        )?;
+
+        if let Some((width, height)) = self.max_size {
+            webview.set_max_size(width, height);
+        }
+        if self.aspect_ratio.is_some() {
+            webview.set_aspect_ratio(self.aspect_ratio);
+        }
+        if self.window_type != WindowType::Normal {
+            webview.set_window_type(self.window_type);
+        }
+        if self.transparent {
+            unsafe { webview_set_transparent(webview.inner.unwrap(), 1) };
+        }
+        if let Some(mut saved) = saved {
+            saved.clamp_to(&webview.monitors());
+            webview.set_size(saved.width, saved.height);
+            webview.set_position(saved.x, saved.y);
+        } else if let Some((x, y)) = self.position {
+            webview.set_position(x, y);
+        }
+        if show_later && self.visible {
+            webview.set_visible(true);
+        }
+        if let Some(ref mut persist) = persist {
+            persist.webview = webview.inner.unwrap();
+            match saved {
+                Some(saved) => persist.state = saved,
+                None => {
+                    let (x, y) = webview.position();
+                    persist.state.x = x;
+                    persist.state.y = y;
+                }
+            }
+        }
+        webview.user_data_wrapper_mut().persist = persist;
+        if let Some(ref icon) = self.icon {
+            webview.set_icon(icon)?;
+        }
+        if self.always_on_top {
+            webview.set_always_on_top(true);
+        }
+        if self.skip_taskbar {
+            webview.set_skip_taskbar(true);
+        }
+        if self.skip_pager {
+            webview.set_skip_pager(true);
+        }
+        if self.sticky {
+            webview.set_sticky(true);
+        }
+        if let Some(saved) = saved {
+            webview.set_maximized(saved.maximized);
+            webview.set_fullscreen(saved.fullscreen);
+            webview.set_zoom_level(saved.zoom_level);
+        }
+        webview.add_user_script(
+            event::EVENT_BRIDGE,
+            InjectAt::DocumentStart,
+            Frames::TopOnly,
+        )?;
+        if !self.rpc_handlers.is_empty() {
+            webview.user_data_wrapper_mut().rpc_handlers = self.rpc_handlers;
+            webview.add_user_script(rpc::RPC_BRIDGE, InjectAt::DocumentStart, Frames::TopOnly)?;
+        }
+        for script in self.user_scripts {
+            webview.add_user_script(script.source, script.inject_at, script.frames)?;
+        }
+        {
+            let user_data = webview.user_data_wrapper_mut();
+            user_data.on_load_started = self.on_load_started;
+            user_data.on_load_committed = self.on_load_committed;
+            user_data.on_load_finished = self.on_load_finished;
+            user_data.on_load_failed = self.on_load_failed;
+            user_data.on_window_event = self.on_window_event;
+            user_data.on_close_requested = self.on_close_requested;
+            user_data.base_url = base_url;
+        }
+        for scheme in scheme_handlers.keys() {
+            let scheme = CString::new(scheme.as_str())?;
+            let ret = unsafe {
+                webview_register_scheme(
+                    webview.inner.unwrap(),
+                    scheme.as_ptr(),
+                    Some(ffi_scheme_handler::<T>),
+                )
+            };
+            if ret != 0 {
+                return Err(Error::SchemeRegistration);
+            }
+        }
+        webview.user_data_wrapper_mut().scheme_handlers = scheme_handlers;
+        if let Some(navigation_handler) = self.navigation_handler {
+            webview.user_data_wrapper_mut().navigation_handler = Some(navigation_handler);
+            unsafe {
+                webview_set_navigation_cb(webview.inner.unwrap(), Some(ffi_navigation_handler::<T>))
+            };
+        }
+        if let Some(new_window_handler) = self.new_window_handler {
+            webview.user_data_wrapper_mut().new_window_handler = Some(new_window_handler);
+            unsafe {
+                webview_set_new_window_cb(webview.inner.unwrap(), Some(ffi_new_window_handler::<T>))
+            };
+        }
+        // The content is only loaded now, so its first page already sees the schemes, scripts and
+        // handlers set up above.
+        if let Some(url) = url {
+            unsafe { webview_navigate(webview.inner.unwrap(), url.as_ptr()) };
+        }
+        if let Some((url, dir)) = file_access {
+            unsafe { webview_load_file(webview.inner.unwrap(), url.as_ptr(), dir.as_ptr()) };
+        }
+        if let Some(html) = html {
+            unsafe { webview_set_html(webview.inner.unwrap(), html.as_ptr(), webview.base_url()) };
+        }
+
+        Ok(webview)
     }
 
     /// Validates provided arguments and runs a new WebView to completion, returning the user data.
@@ -307,13 +1024,91 @@ where
     WebViewBuilder::new()
 }
 
+/// The invoke handler type of a [`WebViewBuilder`] without one, which is only allowed if it has
+/// RPC handlers.
+///
+/// [`WebViewBuilder`]: struct.WebViewBuilder.html
+pub type NoInvokeHandler<T> = fn(&mut WebView<T>, &str) -> WVResult;
+
+/// A callback registered with [`WebViewBuilder::on_load_started()`] and similar methods.
+///
+/// [`WebViewBuilder::on_load_started()`]: struct.WebViewBuilder.html#method.on_load_started
+pub type LoadHandler<'a, T> = Box<dyn FnMut(&mut WebView<T>, &str) -> WVResult + 'a>;
+
+/// A callback registered with [`WebViewBuilder::on_load_failed()`].
+///
+/// [`WebViewBuilder::on_load_failed()`]: struct.WebViewBuilder.html#method.on_load_failed
+pub type LoadFailedHandler<'a, T> = Box<dyn FnMut(&mut WebView<T>, &str, &str) -> WVResult + 'a>;
+
+/// The state of a view that its handles share. Windows opened by the page have their own, as they
+/// are closed before the `WebView` that opened them.
+struct View {
+    // `false` once the view is closed. Locked while dispatching to it, so closing the view waits
+    // for the dispatches in flight.
+    live: Arc<RwLock<bool>>,
+    // Bumped when the view starts loading a page, which cancels the RPC calls of the last one.
+    page: Arc<AtomicUsize>,
+}
+
+impl View {
+    fn new() -> View {
+        View {
+            live: Arc::new(RwLock::new(true)),
+            page: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+extern "C" fn ffi_view_close_handler(arg: *mut c_void) {
+    unsafe {
+        let view = Box::<View>::from_raw(arg as _);
+        let mut live = match view.live.write() {
+            Ok(live) => live,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        *live = false;
+    }
+}
+
+/// Returns `true` once `webview` is closed, after which closures dispatched to it before must not
+/// be run. Only windows opened by the page are closed before they are freed.
+unsafe fn is_closed(webview: *mut CWebView) -> bool {
+    webview_get_view_data(webview).is_null()
+}
+
 struct UserData<'a, T> {
     inner: T,
-    live: Arc<RwLock<()>>,
     invoke_handler: Box<dyn FnMut(&mut WebView<T>, &str) -> WVResult + 'a>,
+    rpc_handlers: HashMap<String, RpcHandler<'a, T>>,
+    navigation_handler: Option<NavigationHandler<'a>>,
+    new_window_handler: Option<NewWindowHandler<'a>>,
+    // Must outlive the call of the new window handler it was returned from.
+    new_window_title: Option<CString>,
+    on_load_started: Option<LoadHandler<'a, T>>,
+    on_load_committed: Option<LoadHandler<'a, T>>,
+    on_load_finished: Option<LoadHandler<'a, T>>,
+    on_load_failed: Option<LoadFailedHandler<'a, T>>,
+    on_window_event: Option<WindowEventHandler<'a, T>>,
+    on_close_requested: Option<CloseRequestedHandler<'a, T>>,
+    scheme_handlers: HashMap<String, SchemeHandler<'a>>,
+    // Set with `WebViewBuilder::base_url()`, used for any HTML set without one.
+    base_url: Option<CString>,
+    persist: Option<Persist>,
+    tasks: HashMap<usize, (LocalFuture<'a>, Arc<TaskWaker>)>,
+    next_task_id: usize,
     result: WVResult,
 }
 
+impl<'a, T> UserData<'a, T> {
+    /// Keeps the result of a handler for the next call to `step()`. The first error is kept
+    /// until then, so neither a later success nor a later error hides it.
+    fn report(&mut self, result: WVResult) {
+        if self.result.is_ok() {
+            self.result = result;
+        }
+    }
+}
+
 /// An owned webview instance.
 ///
 /// Construct via a [`WebViewBuilder`].
@@ -329,7 +1124,6 @@ impl<'a, T> WebView<'a, T> {
     #![cfg_attr(feature = "cargo-clippy", allow(clippy::too_many_arguments))]
     fn new<I>(
         title: &CStr,
-        url: &CStr,
         width: i32,
         height: i32,
         resizable: bool,
@@ -347,8 +1141,22 @@ impl<'a, T> WebView<'a, T> {
     {
         let user_data = Box::new(UserData {
             inner: user_data,
-            live: Arc::new(RwLock::new(())),
             invoke_handler: Box::new(invoke_handler),
+            rpc_handlers: HashMap::new(),
+            navigation_handler: None,
+            new_window_handler: None,
+            new_window_title: None,
+            on_load_started: None,
+            on_load_committed: None,
+            on_load_finished: None,
+            on_load_failed: None,
+            on_window_event: None,
+            on_close_requested: None,
+            scheme_handlers: HashMap::new(),
+            base_url: None,
+            persist: None,
+            tasks: HashMap::new(),
+            next_task_id: 0,
             result: Ok(()),
         });
         let user_data_ptr = Box::into_raw(user_data);
@@ -356,7 +1164,8 @@ impl<'a, T> WebView<'a, T> {
         unsafe {
             let inner = webview_new(
                 title.as_ptr(),
-                url.as_ptr(),
+                // The content is loaded once the builder has set the WebView up.
+                ptr::null(),
                 width,
                 height,
                 resizable as _,
@@ -374,6 +1183,8 @@ impl<'a, T> WebView<'a, T> {
                 Box::<UserData<T>>::from_raw(user_data_ptr);
                 Err(Error::Initialization)
             } else {
+                webview_set_load_cb(inner, Some(ffi_load_handler::<T>));
+                webview_set_window_event_cb(inner, Some(ffi_window_event_handler::<T>));
                 Ok(WebView::from_ptr(inner))
             }
         }
@@ -386,19 +1197,162 @@ impl<'a, T> WebView<'a, T> {
         }
     }
 
+    /// Returns the state shared with the handles of this view, or `None` once it is closed.
+    fn view(&self) -> Option<&View> {
+        unsafe {
+            let inner = self.inner.unwrap();
+            let view = webview_get_view_data(inner) as *const View;
+            if !view.is_null() {
+                return Some(&*view);
+            }
+            // Created once needed, as windows opened by the page aren't known here.
+            let view = Box::into_raw(Box::new(View::new()));
+            if webview_set_view_data(inner, view as _, ffi_view_close_handler) != 0 {
+                Box::from_raw(view);
+                return None;
+            }
+            Some(&*view)
+        }
+    }
+
+    /// Returns the liveness guard of this view, which can't be upgraded once it is closed.
+    fn live(&self) -> Weak<RwLock<bool>> {
+        self.view()
+            .map_or_else(Weak::new, |view| Arc::downgrade(&view.live))
+    }
+
     /// Creates a thread-safe [`Handle`] to the `WebView`, from which closures can be dispatched.
     ///
     /// [`Handle`]: struct.Handle.html
     pub fn handle(&self) -> Handle<T> {
         Handle {
             inner: self.inner.unwrap(),
-            live: Arc::downgrade(&self.user_data_wrapper().live),
+            live: self.live(),
             _phantom: PhantomData,
         }
     }
 
-    fn user_data_wrapper_ptr(&self) -> *mut UserData<'a, T> {
-        unsafe { webview_get_user_data(self.inner.unwrap()) as _ }
+    /// Calls `f` on the `WebView`'s thread once `timeout` has passed, unless the timer is cleared
+    /// before with [`clear_timer()`].
+    ///
+    /// Like with the invoke handler, an `Err` returned by `f` is returned by the next call to
+    /// [`step()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Timer`] if the backend doesn't support timers, which is only GTK so far.
+    ///
+    /// [`clear_timer()`]: #method.clear_timer
+    /// [`step()`]: #method.step
+    /// [`Error::Timer`]: enum.Error.html#variant.Timer
+    pub fn set_timeout<F>(&mut self, timeout: Duration, f: F) -> WVResult<TimerId>
+    where
+        F: FnOnce(&mut WebView<T>) -> WVResult + 'a,
+    {
+        let mut f = Some(f);
+        self.add_timer(timeout, false, move |webview| match f.take() {
+            Some(f) => f(webview),
+            None => Ok(()),
+        })
+    }
+
+    /// Calls `f` on the `WebView`'s thread every `interval`, until the timer is cleared with
+    /// [`clear_timer()`] or the `WebView` is dropped.
+    ///
+    /// Like with the invoke handler, an `Err` returned by `f` is returned by the next call to
+    /// [`step()`], the timer keeps running.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Timer`] if the backend doesn't support timers, which is only GTK so far.
+    ///
+    /// [`clear_timer()`]: #method.clear_timer
+    /// [`step()`]: #method.step
+    /// [`Error::Timer`]: enum.Error.html#variant.Timer
+    pub fn set_interval<F>(&mut self, interval: Duration, f: F) -> WVResult<TimerId>
+    where
+        F: FnMut(&mut WebView<T>) -> WVResult + 'a,
+    {
+        self.add_timer(interval, true, f)
+    }
+
+    /// Stops a timer. Clearing a timer that has already finished does nothing.
+    pub fn clear_timer(&mut self, id: TimerId) {
+        unsafe { webview_remove_timer(self.inner.unwrap(), id.0) };
+    }
+
+    fn add_timer<F>(&mut self, interval: Duration, repeat: bool, f: F) -> WVResult<TimerId>
+    where
+        F: FnMut(&mut WebView<T>) -> WVResult + 'a,
+    {
+        let timer = Box::new(Timer {
+            callback: Box::new(f),
+            repeat,
+        });
+        let interval_ms = interval.as_millis().min(c_int::MAX as u128) as c_int;
+        let id = unsafe {
+            webview_add_timer(
+                self.inner.unwrap(),
+                interval_ms,
+                ffi_timer_handler::<T>,
+                Box::into_raw(timer) as _,
+                ffi_timer_close_handler::<T>,
+            )
+        };
+        if id < 0 {
+            Err(Error::Timer)
+        } else {
+            Ok(TimerId(id))
+        }
+    }
+
+    /// Runs a future on the `WebView`'s thread, driven by its event loop.
+    ///
+    /// The future doesn't need to be `Send`, and can use the `WebView` between awaits through the
+    /// [`LocalHandle`] passed to `f`. It is first polled by the next iteration of the event loop,
+    /// and dropped along with the `WebView` if it hasn't completed by then. Like with the invoke
+    /// handler, an `Err` it resolves to is returned by the next call to [`step()`].
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// webview.spawn_local(|handle| async move {
+    ///     let answer = fetch_answer().await;
+    ///     handle.with(|webview| webview.set_title(&answer))?
+    /// });
+    /// ```
+    ///
+    /// [`LocalHandle`]: struct.LocalHandle.html
+    /// [`step()`]: #method.step
+    pub fn spawn_local<F, Fut>(&mut self, f: F)
+    where
+        F: FnOnce(LocalHandle<T>) -> Fut,
+        Fut: Future<Output = WVResult> + 'a,
+    {
+        let inner = self.inner.unwrap();
+        let live = self.live();
+        let local_handle = LocalHandle {
+            inner,
+            live: live.clone(),
+            _phantom: PhantomData,
+        };
+        let future = Box::pin(f(local_handle));
+        let user_data = self.user_data_wrapper_mut();
+        let id = user_data.next_task_id;
+        user_data.next_task_id += 1;
+        let waker = Arc::new(TaskWaker {
+            id,
+            inner,
+            live,
+            poll: ffi_poll_handler::<T>,
+            scheduled: AtomicBool::new(false),
+        });
+        user_data.tasks.insert(id, (future, waker.clone()));
+        Waker::from(waker).wake();
+    }
+
+    fn user_data_wrapper_ptr(&self) -> *mut UserData<'a, T> {
+        unsafe { webview_get_user_data(self.inner.unwrap()) as _ }
     }
 
     fn user_data_wrapper(&self) -> &UserData<'a, T> {
@@ -434,6 +1388,61 @@ impl<'a, T> WebView<'a, T> {
         unsafe { webview_exit(self.inner.unwrap()) }
     }
 
+    /// Delivers `payload` to the listeners the page registered for `event` with
+    /// `window.webview.listen(event, callback)`.
+    ///
+    /// The payload is serialized to JSON, so it arrives as the equivalent JavaScript value.
+    /// `listen` returns a function which unsubscribes the callback again. `window.webview` is
+    /// defined before the page's own scripts run, except on MSHTML where it only appears once the
+    /// page has loaded. Scripts meant to work there too can subscribe once the `webviewready`
+    /// event has fired on `window`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// webview.emit("progress", &(done, total))?;
+    /// ```
+    ///
+    /// ```javascript
+    /// window.addEventListener('webviewready', function() {
+    ///     webview.listen('progress', function([done, total]) { /* ... */ });
+    /// });
+    /// ```
+    pub fn emit<S: Serialize>(&mut self, event: &str, payload: &S) -> WVResult {
+        self.eval(&event::emit_js(event, payload)?)
+    }
+
+    /// Adds a script which runs in every page loaded from now on, including after navigating or
+    /// reloading. Pages that are already loaded are not affected.
+    ///
+    /// Only GTK and Cocoa honour `frames`; EdgeHTML runs the script in the top frame. MSHTML
+    /// cannot inject scripts, so the script is evaluated once in the current page instead.
+    pub fn add_user_script(
+        &mut self,
+        source: &str,
+        inject_at: InjectAt,
+        frames: Frames,
+    ) -> WVResult {
+        let js = CString::new(source)?;
+        let inject_at = match inject_at {
+            InjectAt::DocumentStart => WEBVIEW_INJECT_AT_DOCUMENT_START,
+            InjectAt::DocumentEnd => WEBVIEW_INJECT_AT_DOCUMENT_END,
+        };
+        let ret = unsafe {
+            webview_add_user_script(
+                self.inner.unwrap(),
+                js.as_ptr(),
+                inject_at,
+                (frames == Frames::All) as _,
+            )
+        };
+        if ret != 0 {
+            self.eval(source)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Executes the provided string as JavaScript code within the `WebView` instance.
     pub fn eval(&mut self, js: &str) -> WVResult {
         let js = CString::new(js)?;
@@ -445,14 +1454,167 @@ impl<'a, T> WebView<'a, T> {
         }
     }
 
+    /// Executes the provided string as JavaScript code within the `WebView` instance, returning a
+    /// future resolving to its completion value converted to JSON.
+    ///
+    /// This doesn't wait for the code to run: the future is resolved by the event loop, so await
+    /// it in a task run by [`spawn_local()`], or from another thread. Before the page has loaded,
+    /// the code runs once it has.
+    ///
+    /// The future resolves to `None` if the value is `undefined` or has no JSON representation,
+    /// such as a function.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// webview.spawn_local(|handle| async move {
+    ///     let eval = handle.with(|webview| Ok(webview.eval_with_result("document.title")))?;
+    ///     println!("{:?}", eval.await?);
+    ///     Ok(())
+    /// });
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the code throws, or its value can't be converted to JSON, e.g. because it is cyclic,
+    /// resolves to [`Error::JsException`] containing the exception message.
+    ///
+    /// Resolves to [`Error::Unsupported`] on platforms other than GTK, which can't return results
+    /// yet.
+    ///
+    /// [`spawn_local()`]: #method.spawn_local
+    /// [`Error::JsException`]: enum.Error.html#variant.JsException
+    /// [`Error::Unsupported`]: enum.Error.html#variant.Unsupported
+    pub fn eval_with_result(&mut self, js: &str) -> impl Future<Output = WVResult<Option<Value>>> {
+        let (sender, result) = dispatch::channel();
+        self.eval_with_callback(js, move |json| {
+            sender.send(json.and_then(|json| match json {
+                Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+                None => Ok(None),
+            }))
+        });
+        result
+    }
+
+    /// Executes the provided string as JavaScript code within the `WebView` instance, returning a
+    /// future resolving to its completion value deserialized into `R`.
+    ///
+    /// Values without a JSON representation, such as `undefined`, are deserialized from `null`,
+    /// e.g. into `None` or `()`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let title: String = handle
+    ///     .with(|webview| Ok(webview.eval_as("document.title")))?
+    ///     .await?;
+    /// let (width, height): (u32, u32) = handle
+    ///     .with(|webview| Ok(webview.eval_as("[innerWidth, innerHeight]")))?
+    ///     .await?;
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Same as [`eval_with_result()`], and additionally resolves to [`Error::Json`] if the value
+    /// cannot be deserialized into `R`.
+    ///
+    /// [`eval_with_result()`]: struct.WebView.html#method.eval_with_result
+    /// [`Error::Json`]: enum.Error.html#variant.Json
+    pub fn eval_as<R: DeserializeOwned + 'static>(
+        &mut self,
+        js: &str,
+    ) -> impl Future<Output = WVResult<R>> {
+        let (sender, result) = dispatch::channel();
+        self.eval_with_callback(js, move |json| {
+            sender.send(json.and_then(|json| match json {
+                Some(json) => Ok(serde_json::from_str(&json)?),
+                None => Ok(serde_json::from_value(Value::Null)?),
+            }))
+        });
+        result
+    }
+
+    /// Runs `js` and calls `f` from the event loop with its completion value as JSON.
+    fn eval_with_callback<F>(&mut self, js: &str, f: F)
+    where
+        F: FnOnce(WVResult<Option<String>>) + 'static,
+    {
+        let js = match CString::new(js) {
+            Ok(js) => js,
+            Err(error) => return f(Err(error.into())),
+        };
+        let callback: Box<EvalCallback> = Box::new(Box::new(f));
+        let arg = Box::into_raw(callback);
+        let ret = unsafe {
+            webview_eval_with_result(
+                self.inner.unwrap(),
+                js.as_ptr(),
+                Some(ffi_eval_result_handler),
+                arg as _,
+            )
+        };
+        // The backend returns without calling back if it can't evaluate with a result.
+        if ret != 0 {
+            let callback = unsafe { Box::from_raw(arg) };
+            callback(Err(Error::Unsupported(
+                "Evaluating JavaScript with a result",
+            )));
+        }
+    }
+
     /// Injects the provided string as CSS within the `WebView` instance.
+    ///
+    /// The CSS only applies to the current page. See [`add_style_sheet()`] for CSS which persists
+    /// across navigations and can be removed again.
+    ///
+    /// [`add_style_sheet()`]: struct.WebView.html#method.add_style_sheet
     pub fn inject_css(&mut self, css: &str) -> WVResult {
         let inject_func = format!("{}({})", CSS_INJECT_FUNCTION, escape(css));
         self.eval(&inject_func).map_err(|_| Error::CssInjection)
     }
 
+    /// Adds a style sheet which applies to the current page and every page loaded afterwards,
+    /// until it is removed with [`remove_style_sheet()`].
+    ///
+    /// Swapping style sheets, e.g. to switch between light and dark themes, restyles the page
+    /// without reloading it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::CssInjection`] on platforms other than GTK, which do not support user
+    /// style sheets. Use [`inject_css()`] there instead.
+    ///
+    /// [`remove_style_sheet()`]: struct.WebView.html#method.remove_style_sheet
+    /// [`inject_css()`]: struct.WebView.html#method.inject_css
+    /// [`Error::CssInjection`]: enum.Error.html#variant.CssInjection
+    pub fn add_style_sheet(&mut self, css: &str, level: StyleLevel) -> WVResult<StyleSheet> {
+        let css = CString::new(css)?;
+        let level = match level {
+            StyleLevel::User => WEBVIEW_STYLE_LEVEL_USER,
+            StyleLevel::Author => WEBVIEW_STYLE_LEVEL_AUTHOR,
+        };
+        let id = unsafe { webview_add_style_sheet(self.inner.unwrap(), css.as_ptr(), level) };
+        if id < 0 {
+            Err(Error::CssInjection)
+        } else {
+            Ok(StyleSheet(id))
+        }
+    }
+
+    /// Removes a style sheet added with [`add_style_sheet()`] from the current and future pages.
+    ///
+    /// Removing a style sheet twice does nothing.
+    ///
+    /// [`add_style_sheet()`]: struct.WebView.html#method.add_style_sheet
+    pub fn remove_style_sheet(&mut self, style_sheet: StyleSheet) {
+        unsafe { webview_remove_style_sheet(self.inner.unwrap(), style_sheet.0) }
+    }
+
     /// Sets the color of the title bar.
     ///
+    /// On GTK this is the background of the page instead, and alpha only lets the desktop show
+    /// through in a window built with [`transparent()`].
+    ///
     /// # Examples
     ///
     /// Without specifying alpha (defaults to 255):
@@ -464,6 +1626,8 @@ impl<'a, T> WebView<'a, T> {
     /// ```ignore
     /// webview.set_color((123, 321, 213, 127));
     /// ```
+    ///
+    /// [`transparent()`]: struct.WebViewBuilder.html#method.transparent
     pub fn set_color<C: Into<Color>>(&mut self, color: C) {
         let color = color.into();
         unsafe { webview_set_color(self.inner.unwrap(), color.r, color.g, color.b, color.a) }
@@ -472,21 +1636,156 @@ impl<'a, T> WebView<'a, T> {
     /// Sets the page native browser zoom level.
     pub fn set_zoom_level(&mut self, percentage: f64) {
         unsafe { webview_set_zoom_level(self.inner.unwrap(), percentage) }
+        let inner = self.inner.unwrap();
+        if let Some(ref mut persist) = self.user_data_wrapper_mut().persist {
+            if persist.webview == inner {
+                persist.state.zoom_level = percentage;
+            } else {
+                return;
+            }
+        }
+        self.save_window_state_later();
+    }
+
+    /// Saves the persisted window state once it hasn't changed for a second, so changes are kept
+    /// even if the process is killed. Without timers, it is only saved when the window is closed.
+    fn save_window_state_later(&mut self) {
+        let pending = match self.user_data_wrapper_mut().persist {
+            Some(ref mut persist) => persist.save_timer.take(),
+            None => return,
+        };
+        if let Some(id) = pending {
+            self.clear_timer(id);
+        }
+        let timer = self.add_timer(Duration::from_secs(1), false, |webview| {
+            webview.save_window_state();
+            Ok(())
+        });
+        if let Some(ref mut persist) = self.user_data_wrapper_mut().persist {
+            persist.save_timer = timer.ok();
+        }
+    }
+
+    /// Saves the persisted window state, with the zoom level the user may have changed.
+    fn save_window_state(&mut self) {
+        if let Some(ref mut persist) = self.user_data_wrapper_mut().persist {
+            persist.save_timer = None;
+            let zoom_level = unsafe { webview_get_zoom_level(persist.webview) };
+            if zoom_level > 0.0 {
+                persist.state.zoom_level = zoom_level;
+            }
+            persist.save();
+        }
     }
 
     /// Sets the page HTML directly.
     ///
+    /// Relative URLs in it are resolved against the URL set with
+    /// [`WebViewBuilder::base_url()`], or `about:blank` without one. Use
+    /// [`set_html_with_base_url()`] for another base URL.
+    ///
     /// # Errors
     ///
     /// If `html` contain a nul byte, returns [`Error::NulByte`].
     ///
+    /// [`WebViewBuilder::base_url()`]: struct.WebViewBuilder.html#method.base_url
+    /// [`set_html_with_base_url()`]: #method.set_html_with_base_url
     /// [`Error::NulByte`]: enum.Error.html#variant.NulByte
     pub fn set_html(&mut self, html: &str) -> WVResult {
         let html = CString::new(html)?;
-        unsafe { webview_set_html(self.inner.unwrap(), html.as_ptr()) }
+        unsafe { webview_set_html(self.inner.unwrap(), html.as_ptr(), self.base_url()) }
+        Ok(())
+    }
+
+    /// Returns the base URL of HTML set without one.
+    fn base_url(&self) -> *const c_char {
+        match self.user_data_wrapper().base_url {
+            Some(ref base_url) => base_url.as_ptr(),
+            None => ABOUT_BLANK.as_ptr() as *const c_char,
+        }
+    }
+
+    /// Sets the page HTML directly, resolving relative URLs in it against `base_url`.
+    ///
+    /// On Windows the base URL is ignored.
+    ///
+    /// # Errors
+    ///
+    /// If `html` or `base_url` contain a nul byte, returns [`Error::NulByte`].
+    ///
+    /// [`Error::NulByte`]: enum.Error.html#variant.NulByte
+    pub fn set_html_with_base_url(&mut self, html: &str, base_url: &str) -> WVResult {
+        let html = CString::new(html)?;
+        let base_url = CString::new(base_url)?;
+        unsafe { webview_set_html(self.inner.unwrap(), html.as_ptr(), base_url.as_ptr()) }
+        Ok(())
+    }
+
+    /// Navigates to `url`, as if the user had followed a link to it.
+    ///
+    /// # Errors
+    ///
+    /// If `url` contain a nul byte, returns [`Error::NulByte`].
+    ///
+    /// [`Error::NulByte`]: enum.Error.html#variant.NulByte
+    pub fn navigate(&mut self, url: &str) -> WVResult {
+        let url = CString::new(url)?;
+        unsafe { webview_navigate(self.inner.unwrap(), url.as_ptr()) }
         Ok(())
     }
 
+    /// Reloads the current page. With `bypass_cache`, cached resources are fetched again as
+    /// well (not supported by EdgeHTML).
+    pub fn reload(&mut self, bypass_cache: bool) {
+        unsafe { webview_reload(self.inner.unwrap(), bypass_cache as _) }
+    }
+
+    /// Navigates to the previous page in the history, if any.
+    pub fn go_back(&mut self) {
+        unsafe { webview_go_back(self.inner.unwrap()) }
+    }
+
+    /// Navigates to the next page in the history, if any.
+    pub fn go_forward(&mut self) {
+        unsafe { webview_go_forward(self.inner.unwrap()) }
+    }
+
+    /// Returns `true` if there is a previous page in the history. Always `true` on MSHTML.
+    pub fn can_go_back(&self) -> bool {
+        unsafe { webview_can_go_back(self.inner.unwrap()) != 0 }
+    }
+
+    /// Returns `true` if there is a next page in the history. Always `true` on MSHTML.
+    pub fn can_go_forward(&self) -> bool {
+        unsafe { webview_can_go_forward(self.inner.unwrap()) != 0 }
+    }
+
+    /// Stops loading the current page.
+    pub fn stop_loading(&mut self) {
+        unsafe { webview_stop_loading(self.inner.unwrap()) }
+    }
+
+    /// Returns an estimate of how much of the current page has been loaded, from `0.0` to `1.0`.
+    ///
+    /// Always `1.0` on Windows.
+    pub fn load_progress(&self) -> f64 {
+        unsafe { webview_get_load_progress(self.inner.unwrap()) }
+    }
+
+    /// Returns the URL of the current page, or `None` if nothing has been loaded yet.
+    pub fn url(&self) -> Option<String> {
+        unsafe { string_from_ptr(webview_get_url(self.inner.unwrap())) }
+    }
+
+    /// Returns the title of the current page, or `None` if nothing has been loaded yet.
+    ///
+    /// This is the page's `<title>`, not the title of the window set by [`set_title()`].
+    ///
+    /// [`set_title()`]: struct.WebView.html#method.set_title
+    pub fn title(&self) -> Option<String> {
+        unsafe { string_from_ptr(webview_get_page_title(self.inner.unwrap())) }
+    }
+
     /// Sets the title displayed at the top of the window.
     ///
     /// # Errors
@@ -520,6 +1819,117 @@ impl<'a, T> WebView<'a, T> {
         unsafe { webview_set_visible(self.inner.unwrap(), visible as _) };
     }
 
+    /// Sets the icon of the window. Ignored on macOS, where windows show the icon of the app
+    /// bundle instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Icon`] if the icon can't be decoded.
+    ///
+    /// [`Error::Icon`]: enum.Error.html#variant.Icon
+    pub fn set_icon(&mut self, icon: &Icon) -> WVResult {
+        let (width, height) = icon.size.unwrap_or((0, 0));
+        let ret = unsafe {
+            webview_set_icon(
+                self.inner.unwrap(),
+                icon.data.as_ptr(),
+                icon.data.len(),
+                width as c_int,
+                height as c_int,
+            )
+        };
+        if ret != 0 {
+            return Err(Error::Icon);
+        }
+        Ok(())
+    }
+
+    /// Keeps the window above other windows, or stops doing so.
+    pub fn set_always_on_top(&mut self, always_on_top: bool) {
+        unsafe { webview_set_always_on_top(self.inner.unwrap(), always_on_top as _) };
+    }
+
+    /// Hides the window from the taskbar, or shows it there again. Only supported on Linux so
+    /// far.
+    pub fn set_skip_taskbar(&mut self, skip: bool) {
+        unsafe { webview_set_skip_taskbar(self.inner.unwrap(), skip as _) };
+    }
+
+    /// Hides the window from the workspace switcher, or shows it there again. Only supported on
+    /// Linux.
+    pub fn set_skip_pager(&mut self, skip: bool) {
+        unsafe { webview_set_skip_pager(self.inner.unwrap(), skip as _) };
+    }
+
+    /// Shows the window on all workspaces, or only on the current one. Not supported on Windows
+    /// yet.
+    pub fn set_sticky(&mut self, sticky: bool) {
+        unsafe { webview_set_sticky(self.inner.unwrap(), sticky as _) };
+    }
+
+    /// Tells the window manager what the window is used for. Most window managers ignore changes
+    /// while the window is shown, so a shown window is hidden and shown again, at the same
+    /// position. Only supported on GTK so far.
+    pub fn set_window_type(&mut self, window_type: WindowType) {
+        unsafe { webview_set_window_type(self.inner.unwrap(), window_type.to_raw()) };
+    }
+
+    /// Returns the size of the window's content.
+    ///
+    /// On macOS this is the size the window was last given, as it isn't read back yet.
+    pub fn size(&self) -> (i32, i32) {
+        let (mut width, mut height) = (0, 0);
+        unsafe { webview_get_size(self.inner.unwrap(), &mut width, &mut height) };
+        (width, height)
+    }
+
+    /// Resizes the window so its content has the given size.
+    pub fn set_size(&mut self, width: i32, height: i32) {
+        unsafe { webview_set_size(self.inner.unwrap(), width, height) };
+    }
+
+    /// Returns the position of the top left corner of the window on the screen.
+    ///
+    /// Always `(0, 0)` on macOS.
+    pub fn position(&self) -> (i32, i32) {
+        let (mut x, mut y) = (0, 0);
+        unsafe { webview_get_position(self.inner.unwrap(), &mut x, &mut y) };
+        (x, y)
+    }
+
+    /// Moves the top left corner of the window to the given position on the screen. Ignored on
+    /// macOS.
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        unsafe { webview_set_position(self.inner.unwrap(), x, y) };
+    }
+
+    /// Centers the window on its monitor.
+    pub fn center(&mut self) {
+        unsafe { webview_center(self.inner.unwrap()) };
+    }
+
+    /// Sets the maximum size of the window. A negative width or height leaves that dimension
+    /// unlimited.
+    pub fn set_max_size(&mut self, width: i32, height: i32) {
+        unsafe { webview_set_max_size(self.inner.unwrap(), width, height) };
+    }
+
+    /// Keeps the width of the window divided by its height at `ratio` when the user resizes it,
+    /// or lets it change freely again with `None`. Not enforced on Windows.
+    pub fn set_aspect_ratio(&mut self, ratio: Option<f64>) {
+        unsafe { webview_set_aspect_ratio(self.inner.unwrap(), ratio.unwrap_or(0.0)) };
+    }
+
+    fn monitors(&self) -> Vec<CRect> {
+        let inner = self.inner.unwrap();
+        let count = unsafe { webview_get_monitors(inner, ptr::null_mut(), 0) };
+        let mut monitors = vec![CRect::default(); count.max(0) as usize];
+        let count =
+            unsafe { webview_get_monitors(inner, monitors.as_mut_ptr(), monitors.len() as c_int) };
+        monitors.truncate(count.max(0) as usize);
+        monitors
+    }
+
     /// Returns a builder for opening a new dialog window.
     #[deprecated(
         note = "Please use crates like 'tinyfiledialogs' for dialog handling, see example in examples/dialog.rs"
@@ -530,17 +1940,59 @@ impl<'a, T> WebView<'a, T> {
 
     /// Iterates the event loop. Returns `None` if the view has been closed or terminated.
     pub fn step(&mut self) -> Option<WVResult> {
-        unsafe {
-            match webview_loop(self.inner.unwrap(), 1) {
-                0 => {
-                    let closure_result = &mut self.user_data_wrapper_mut().result;
-                    match closure_result {
-                        Ok(_) => Some(Ok(())),
-                        e => Some(mem::replace(e, Ok(()))),
-                    }
+        self.loop_once(true)
+    }
+
+    /// Iterates the event loop without waiting for an event, so the `WebView` can be driven from
+    /// a game loop or another event loop. Returns `None` if the view has been closed or
+    /// terminated.
+    pub fn step_nonblocking(&mut self) -> Option<WVResult> {
+        self.loop_once(false)
+    }
+
+    /// Iterates the event loop, waiting at most `timeout` for an event. Returns `None` if the view
+    /// has been closed or terminated.
+    pub fn step_timeout(&mut self, timeout: Duration) -> Option<WVResult> {
+        let timeout_ms = timeout.as_millis().min(c_int::MAX as u128) as c_int;
+        let should_exit = unsafe { webview_loop_timeout(self.inner.unwrap(), timeout_ms) };
+        self.loop_result(should_exit)
+    }
+
+    /// Iterates the event loop until `predicate` returns `true`. Returns `None` if the view has
+    /// been closed or terminated before.
+    ///
+    /// The predicate is checked before every iteration. As iterations wait for events, a
+    /// predicate depending on other threads should be paired with a [`Handle`] waking the loop
+    /// up, e.g. by dispatching the change of state.
+    ///
+    /// [`Handle`]: struct.Handle.html
+    pub fn run_until<F>(&mut self, mut predicate: F) -> Option<WVResult>
+    where
+        F: FnMut(&mut WebView<'a, T>) -> bool,
+    {
+        while !predicate(self) {
+            if let Err(e) = self.step()? {
+                return Some(Err(e));
+            }
+        }
+        Some(Ok(()))
+    }
+
+    fn loop_once(&mut self, blocking: bool) -> Option<WVResult> {
+        let should_exit = unsafe { webview_loop(self.inner.unwrap(), blocking as c_int) };
+        self.loop_result(should_exit)
+    }
+
+    fn loop_result(&mut self, should_exit: c_int) -> Option<WVResult> {
+        match should_exit {
+            0 => {
+                let closure_result = &mut self.user_data_wrapper_mut().result;
+                match closure_result {
+                    Ok(_) => Some(Ok(())),
+                    e => Some(mem::replace(e, Ok(()))),
                 }
-                _ => None,
             }
+            _ => None,
         }
     }
 
@@ -565,17 +2017,12 @@ impl<'a, T> WebView<'a, T> {
     }
 
     unsafe fn _into_inner(&mut self) -> T {
-        let lock = self
-            .user_data_wrapper()
-            .live
-            .write()
-            .expect("A dispatch channel thread panicked while holding mutex to WebView.");
-
+        self.save_window_state();
         let user_data_ptr = self.user_data_wrapper_ptr();
         webview_exit(self.inner.unwrap());
+        // Closes the view, which waits for the dispatches to it in flight.
         webview_free(self.inner.unwrap());
         let user_data = *Box::from_raw(user_data_ptr);
-        std::mem::drop(lock);
         user_data.inner
     }
 }
@@ -593,10 +2040,13 @@ impl<'a, T> Drop for WebView<'a, T> {
 
 /// A thread-safe handle to a [`WebView`] instance. Used to dispatch closures onto its task queue.
 ///
+/// A handle created in a window opened by the page dispatches to that window, and fails once it
+/// has been closed.
+///
 /// [`WebView`]: struct.WebView.html
 pub struct Handle<T> {
     inner: *mut CWebView,
-    live: Weak<RwLock<()>>,
+    live: Weak<RwLock<bool>>,
     _phantom: PhantomData<T>,
 }
 
@@ -630,7 +2080,10 @@ impl<T> Handle<T> {
         // dispatched.
         let mutex = self.live.upgrade().ok_or(Error::Dispatch)?;
         let closure = Box::new(SendBoxFnOnce::new(f));
-        let _lock = mutex.read().map_err(|_| Error::Dispatch)?;
+        let live = mutex.read().map_err(|_| Error::Dispatch)?;
+        if !*live {
+            return Err(Error::Dispatch);
+        }
 
         // Send closure to webview.
         unsafe {
@@ -642,32 +2095,357 @@ impl<T> Handle<T> {
         }
         Ok(())
     }
+
+    /// Schedules a closure to be run on the [`WebView`] thread, returning a future resolving to
+    /// its result.
+    ///
+    /// This lets tasks of an async runtime query the state of the UI, or await JavaScript
+    /// evaluations. Unlike with [`dispatch()`], an `Err` returned by the closure is only returned
+    /// by the future.
+    ///
+    /// The future resolves to [`Error::Dispatch`] if the [`WebView`] has been dropped before
+    /// running the closure.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let url = handle.dispatch_async(|webview| Ok(webview.url())).await?;
+    /// ```
+    ///
+    /// [`WebView`]: struct.WebView.html
+    /// [`dispatch()`]: #method.dispatch
+    /// [`Error::Dispatch`]: enum.Error.html#variant.Dispatch
+    pub fn dispatch_async<F, R>(&self, f: F) -> impl Future<Output = WVResult<R>>
+    where
+        F: FnOnce(&mut WebView<T>) -> WVResult<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, result) = dispatch::channel();
+        // If dispatching fails, dropping the closure resolves the future with the error.
+        let _ = self.dispatch(move |webview| {
+            sender.send(f(webview));
+            Ok(())
+        });
+        result
+    }
+
+    /// Runs a closure on the [`WebView`] thread and waits for its result.
+    ///
+    /// Must not be called on the [`WebView`] thread itself, which would wait forever.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Dispatch`] if the [`WebView`] has been dropped before running the closure,
+    /// or the `Err` returned by the closure.
+    ///
+    /// [`WebView`]: struct.WebView.html
+    /// [`Error::Dispatch`]: enum.Error.html#variant.Dispatch
+    pub fn dispatch_sync<F, R>(&self, f: F) -> WVResult<R>
+    where
+        F: FnOnce(&mut WebView<T>) -> WVResult<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        self.dispatch(move |webview| {
+            let _ = sender.send(f(webview));
+            Ok(())
+        })?;
+        receiver.recv().map_err(|_| Error::Dispatch)?
+    }
+
+    /// Schedules delivery of `payload` to the page's listeners for `event`.
+    ///
+    /// The payload is serialized on the calling thread. See [`WebView::emit()`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Dispatch`] if the [`WebView`] has been dropped.
+    ///
+    /// [`WebView`]: struct.WebView.html
+    /// [`WebView::emit()`]: struct.WebView.html#method.emit
+    /// [`Error::Dispatch`]: enum.Error.html#variant.Dispatch
+    pub fn emit<S: Serialize>(&self, event: &str, payload: &S) -> WVResult {
+        let js = event::emit_js(event, payload)?;
+        self.dispatch(move |webview| webview.eval(&js))
+    }
 }
 
 unsafe impl<T> Send for Handle<T> {}
 unsafe impl<T> Sync for Handle<T> {}
 
+unsafe fn string_from_ptr(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+    }
+}
+
 extern "C" fn ffi_dispatch_handler<T>(webview: *mut CWebView, arg: *mut c_void) {
     unsafe {
+        if is_closed(webview) {
+            Box::<SendBoxFnOnce<'static, (&mut WebView<T>,), WVResult>>::from_raw(arg as _);
+            return;
+        }
         let mut handle = WebView::<T>::from_ptr(webview);
         let result = {
             let callback =
                 Box::<SendBoxFnOnce<'static, (&mut WebView<T>,), WVResult>>::from_raw(arg as _);
             callback.call(&mut handle)
         };
-        handle.user_data_wrapper_mut().result = result;
+        handle.user_data_wrapper_mut().report(result);
         // Do not clean up the webview on drop of the temporary WebView in handle
         handle.inner = None;
     }
 }
 
+extern "C" fn ffi_poll_handler<T>(webview: *mut CWebView, arg: *mut c_void) {
+    unsafe {
+        // The task is dropped along with the WebView that spawned it.
+        if is_closed(webview) {
+            return;
+        }
+        let user_data = webview_get_user_data(webview) as *mut UserData<T>;
+        // Taken out while polled, so the future can spawn other tasks.
+        let (mut future, task_waker) = match (*user_data).tasks.remove(&(arg as usize)) {
+            Some(task) => task,
+            None => return,
+        };
+        task_waker.scheduled.store(false, Ordering::SeqCst);
+        let waker = Waker::from(task_waker.clone());
+        let poll = task::polling(webview, || {
+            future.as_mut().poll(&mut Context::from_waker(&waker))
+        });
+        match poll {
+            Poll::Ready(Ok(())) => {}
+            Poll::Ready(result) => (*user_data).report(result),
+            Poll::Pending => {
+                (*user_data)
+                    .tasks
+                    .insert(arg as usize, (future, task_waker));
+            }
+        }
+    }
+}
+
+extern "C" fn ffi_timer_handler<T>(webview: *mut CWebView, arg: *mut c_void) -> c_int {
+    unsafe {
+        let timer = &mut *(arg as *mut Timer<T>);
+        let mut handle = WebView::<T>::from_ptr(webview);
+        let result = (timer.callback)(&mut handle);
+        handle.user_data_wrapper_mut().report(result);
+        // Do not clean up the webview on drop of the temporary WebView in handle
+        handle.inner = None;
+        timer.repeat as c_int
+    }
+}
+
+extern "C" fn ffi_timer_close_handler<T>(arg: *mut c_void) {
+    unsafe {
+        let _ = Box::<Timer<T>>::from_raw(arg as _);
+    }
+}
+
 extern "C" fn ffi_invoke_handler<T>(webview: *mut CWebView, arg: *const c_char) {
     unsafe {
         let arg = CStr::from_ptr(arg).to_string_lossy().to_string();
         let mut handle = WebView::<T>::from_ptr(webview);
-        let result = ((*handle.user_data_wrapper_ptr()).invoke_handler)(&mut handle, &arg);
-        handle.user_data_wrapper_mut().result = result;
+        let result = match Message::parse(&arg) {
+            Some(Message::Rpc { id, method, params }) => {
+                rpc::handle_call(&mut handle, id, &method, params)
+            }
+            None => ((*handle.user_data_wrapper_ptr()).invoke_handler)(&mut handle, &arg),
+        };
+        handle.user_data_wrapper_mut().report(result);
+        // Do not clean up the webview on drop of the temporary WebView in handle
+        handle.inner = None;
+    }
+}
+
+extern "C" fn ffi_load_handler<T>(
+    webview: *mut CWebView,
+    event: c_int,
+    url: *const c_char,
+    error: *const c_char,
+) {
+    unsafe {
+        let mut handle = WebView::<T>::from_ptr(webview);
+        let user_data = handle.user_data_wrapper_ptr();
+        let url = string_from_ptr(url).unwrap_or_default();
+        let result = match event {
+            WEBVIEW_LOAD_STARTED => {
+                // Settling pending RPC calls of the previous page would be pointless.
+                if let Some(view) = handle.view() {
+                    view.page.fetch_add(1, Ordering::SeqCst);
+                }
+                match (*user_data).on_load_started {
+                    Some(ref mut handler) => handler(&mut handle, &url),
+                    None => Ok(()),
+                }
+            }
+            WEBVIEW_LOAD_COMMITTED => match (*user_data).on_load_committed {
+                Some(ref mut handler) => handler(&mut handle, &url),
+                None => Ok(()),
+            },
+            WEBVIEW_LOAD_FINISHED => match (*user_data).on_load_finished {
+                Some(ref mut handler) => handler(&mut handle, &url),
+                None => Ok(()),
+            },
+            WEBVIEW_LOAD_FAILED => match (*user_data).on_load_failed {
+                Some(ref mut handler) => handler(
+                    &mut handle,
+                    &url,
+                    &string_from_ptr(error).unwrap_or_default(),
+                ),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        };
+        (*user_data).report(result);
+        // Do not clean up the webview on drop of the temporary WebView in handle
+        handle.inner = None;
+    }
+}
+
+extern "C" fn ffi_window_event_handler<T>(
+    webview: *mut CWebView,
+    event: c_int,
+    a: c_int,
+    b: c_int,
+) -> c_int {
+    unsafe {
+        let mut handle = WebView::<T>::from_ptr(webview);
+        let user_data = handle.user_data_wrapper_ptr();
+        let mut keep_open = false;
+        let result = if event == WEBVIEW_WINDOW_CLOSE_REQUESTED {
+            match (*user_data).on_close_requested {
+                Some(ref mut handler) => match handler(&mut handle) {
+                    Ok(close) => {
+                        keep_open = !close;
+                        Ok(())
+                    }
+                    Err(e) => {
+                        keep_open = true;
+                        Err(e)
+                    }
+                },
+                None => Ok(()),
+            }
+        } else {
+            let event = WindowEvent::from_raw(event, a, b);
+            if let (Some(event), Some(persist)) = (event, &mut (*user_data).persist) {
+                if persist.webview == webview {
+                    let state = persist.state;
+                    persist.update(event);
+                    if persist.state != state {
+                        handle.save_window_state_later();
+                    }
+                }
+            }
+            match (event, &mut (*user_data).on_window_event) {
+                (Some(event), Some(handler)) => handler(&mut handle, event),
+                _ => Ok(()),
+            }
+        };
+        (*user_data).report(result);
         // Do not clean up the webview on drop of the temporary WebView in handle
         handle.inner = None;
+        keep_open as c_int
+    }
+}
+
+extern "C" fn ffi_navigation_handler<T>(
+    webview: *mut CWebView,
+    url: *const c_char,
+    navigation_type: c_int,
+    target_frame: *const c_char,
+    modifiers: c_int,
+) -> c_int {
+    unsafe {
+        let user_data = &mut *(webview_get_user_data(webview) as *mut UserData<T>);
+        let navigation_handler = match user_data.navigation_handler {
+            Some(ref mut navigation_handler) => navigation_handler,
+            None => return WEBVIEW_POLICY_ALLOW,
+        };
+        let request = NavigationRequest {
+            url: string_from_ptr(url).unwrap_or_default(),
+            navigation_type: NavigationType::from_raw(navigation_type),
+            target_frame: string_from_ptr(target_frame),
+            modifiers: Modifiers::from_raw(modifiers),
+        };
+        navigation_handler(&request).into_raw()
+    }
+}
+
+extern "C" fn ffi_new_window_handler<T>(
+    webview: *mut CWebView,
+    url: *const c_char,
+    features: *const CWindowFeatures,
+    config: *mut CWindowConfig,
+) -> c_int {
+    unsafe {
+        let user_data = &mut *(webview_get_user_data(webview) as *mut UserData<T>);
+        let new_window_handler = match user_data.new_window_handler {
+            Some(ref mut new_window_handler) => new_window_handler,
+            None => return WEBVIEW_POLICY_ALLOW,
+        };
+        let request = NewWindowRequest::from_raw(
+            &string_from_ptr(url).unwrap_or_default(),
+            &*features,
+            &*config,
+        );
+        match new_window_handler(&request) {
+            NewWindowPolicy::Open(window) => {
+                // A title with a nul byte keeps the default one.
+                user_data.new_window_title = CString::new(window.title).ok();
+                if let Some(ref title) = user_data.new_window_title {
+                    (*config).title = title.as_ptr();
+                }
+                (*config).width = window.width;
+                (*config).height = window.height;
+                (*config).resizable = window.resizable as c_int;
+                (*config).fullscreen = window.fullscreen as c_int;
+                WEBVIEW_POLICY_ALLOW
+            }
+            NewWindowPolicy::Deny => WEBVIEW_POLICY_DENY,
+            NewWindowPolicy::OpenInSystemBrowser => WEBVIEW_POLICY_OPEN_EXTERNALLY,
+        }
+    }
+}
+
+extern "C" fn ffi_scheme_handler<T>(webview: *mut CWebView, request: *mut CSchemeRequest) {
+    unsafe {
+        let user_data = &mut *(webview_get_user_data(webview) as *mut UserData<T>);
+        let scheme =
+            string_from_ptr(webview_scheme_request_get_scheme(request)).unwrap_or_default();
+        let request_data = scheme::read_request(request);
+        let response = match user_data.scheme_handlers.get_mut(&scheme) {
+            Some(handler) => handler(request_data),
+            None => SchemeResponse::not_found(),
+        };
+        user_data.report(scheme::finish_request(request, response));
+    }
+}
+
+type EvalCallback = Box<dyn FnOnce(WVResult<Option<String>>)>;
+
+extern "C" fn ffi_eval_result_handler(
+    arg: *mut c_void,
+    json: *const c_char,
+    exception: *const c_char,
+) {
+    unsafe {
+        let callback = Box::from_raw(arg as *mut EvalCallback);
+        callback(if exception.is_null() {
+            Ok(if json.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(json).to_string_lossy().to_string())
+            })
+        } else {
+            Err(Error::JsException(
+                CStr::from_ptr(exception).to_string_lossy().to_string(),
+            ))
+        });
     }
 }
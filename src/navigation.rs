@@ -0,0 +1,257 @@
+use ffi::*;
+use std::{ffi::CStr, os::raw::c_int};
+
+/// What caused a navigation.
+///
+/// # Variants
+///
+/// - `LinkClicked` - The user clicked a link.
+/// - `FormSubmitted` - A form was submitted.
+/// - `BackForward` - The user went back or forward in the history.
+/// - `Reload` - The page was reloaded.
+/// - `FormResubmitted` - A form was submitted again, e.g. by reloading its result.
+/// - `Other` - Anything else, such as navigating from Rust or assigning `window.location`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum NavigationType {
+    LinkClicked,
+    FormSubmitted,
+    BackForward,
+    Reload,
+    FormResubmitted,
+    Other,
+}
+
+impl NavigationType {
+    pub(crate) fn from_raw(navigation_type: c_int) -> NavigationType {
+        match navigation_type {
+            WEBVIEW_NAVIGATION_LINK_CLICKED => NavigationType::LinkClicked,
+            WEBVIEW_NAVIGATION_FORM_SUBMITTED => NavigationType::FormSubmitted,
+            WEBVIEW_NAVIGATION_BACK_FORWARD => NavigationType::BackForward,
+            WEBVIEW_NAVIGATION_RELOAD => NavigationType::Reload,
+            WEBVIEW_NAVIGATION_FORM_RESUBMITTED => NavigationType::FormResubmitted,
+            _ => NavigationType::Other,
+        }
+    }
+}
+
+/// The modifier keys held down when a navigation was triggered.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    /// The Command key on macOS, the Windows or Super key elsewhere.
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub(crate) fn from_raw(modifiers: c_int) -> Modifiers {
+        Modifiers {
+            shift: modifiers & WEBVIEW_MODIFIER_SHIFT != 0,
+            control: modifiers & WEBVIEW_MODIFIER_CONTROL != 0,
+            alt: modifiers & WEBVIEW_MODIFIER_ALT != 0,
+            meta: modifiers & WEBVIEW_MODIFIER_META != 0,
+        }
+    }
+}
+
+/// A navigation about to happen, passed to the handler registered with
+/// [`WebViewBuilder::navigation_handler()`].
+///
+/// [`WebViewBuilder::navigation_handler()`]: struct.WebViewBuilder.html#method.navigation_handler
+#[derive(Debug, Clone)]
+pub struct NavigationRequest {
+    pub url: String,
+    pub navigation_type: NavigationType,
+    /// The name of the frame targeted by the navigation if it is not the current one, e.g.
+    /// `_blank` for a link with `target="_blank"`.
+    pub target_frame: Option<String>,
+    pub modifiers: Modifiers,
+}
+
+/// What to do with a [`NavigationRequest`].
+///
+/// # Variants
+///
+/// - `Allow` - Let the navigation happen.
+/// - `Deny` - Stay on the current page.
+/// - `OpenInSystemBrowser` - Stay on the current page and open the URL in the user's default
+///   browser instead.
+/// - `OpenInNewWindow` - Stay on the current page and open the URL in a new window, which shares
+///   the handlers and user data of this one and is closed along with it.
+///
+/// [`NavigationRequest`]: struct.NavigationRequest.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Policy {
+    Allow,
+    Deny,
+    OpenInSystemBrowser,
+    OpenInNewWindow,
+}
+
+/// A handler registered with [`WebViewBuilder::navigation_handler()`].
+///
+/// [`WebViewBuilder::navigation_handler()`]: struct.WebViewBuilder.html#method.navigation_handler
+pub type NavigationHandler<'a> = Box<dyn FnMut(&NavigationRequest) -> Policy + 'a>;
+
+impl Policy {
+    pub(crate) fn into_raw(self) -> c_int {
+        match self {
+            Policy::Allow => WEBVIEW_POLICY_ALLOW,
+            Policy::Deny => WEBVIEW_POLICY_DENY,
+            Policy::OpenInSystemBrowser => WEBVIEW_POLICY_OPEN_EXTERNALLY,
+            Policy::OpenInNewWindow => WEBVIEW_POLICY_OPEN_NEW_WINDOW,
+        }
+    }
+}
+
+/// The size and position a page asked for when opening a window, e.g. with
+/// `window.open(url, "", "width=400,height=300")`.
+///
+/// On GTK, a page asking for the position `0, 0` can't be told apart from one not asking for
+/// any, so `x` and `y` are `None` then.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct WindowFeatures {
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub resizable: bool,
+    pub fullscreen: bool,
+}
+
+impl WindowFeatures {
+    pub(crate) fn from_raw(features: &CWindowFeatures) -> WindowFeatures {
+        let position = |value: c_int| {
+            if features.has_position != 0 {
+                Some(value)
+            } else {
+                None
+            }
+        };
+        let size = |value: c_int| if value > 0 { Some(value) } else { None };
+        WindowFeatures {
+            x: position(features.x),
+            y: position(features.y),
+            width: size(features.width),
+            height: size(features.height),
+            resizable: features.resizable != 0,
+            fullscreen: features.fullscreen != 0,
+        }
+    }
+}
+
+/// How a window opened by a page is shown.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: i32,
+    pub height: i32,
+    pub resizable: bool,
+    pub fullscreen: bool,
+}
+
+/// A window the page is about to open with `window.open` or a link with `target="_blank"`,
+/// passed to the handler registered with [`WebViewBuilder::new_window_handler()`].
+///
+/// [`WebViewBuilder::new_window_handler()`]: struct.WebViewBuilder.html#method.new_window_handler
+#[derive(Debug, Clone)]
+pub struct NewWindowRequest {
+    /// The URL to open, empty for `window.open()` without one.
+    pub url: String,
+    pub features: WindowFeatures,
+    config: WindowConfig,
+}
+
+impl NewWindowRequest {
+    pub(crate) unsafe fn from_raw(
+        url: &str,
+        features: &CWindowFeatures,
+        config: &CWindowConfig,
+    ) -> NewWindowRequest {
+        NewWindowRequest {
+            url: url.to_string(),
+            features: WindowFeatures::from_raw(features),
+            config: WindowConfig {
+                title: if config.title.is_null() {
+                    String::new()
+                } else {
+                    CStr::from_ptr(config.title).to_string_lossy().into_owned()
+                },
+                width: config.width,
+                height: config.height,
+                resizable: config.resizable != 0,
+                fullscreen: config.fullscreen != 0,
+            },
+        }
+    }
+
+    /// Returns the configuration the window gets by default: the title of the window opening it
+    /// and the size the page asked for, falling back to the size of the opening window.
+    pub fn config(&self) -> WindowConfig {
+        self.config.clone()
+    }
+}
+
+/// What to do with a [`NewWindowRequest`].
+///
+/// # Variants
+///
+/// - `Open` - Open the window as configured. It shares the handlers and user data of the window
+///   opening it, and the page can script it like any window it opened.
+/// - `Deny` - Don't open the window.
+/// - `OpenInSystemBrowser` - Open the URL in the user's default browser instead.
+///
+/// [`NewWindowRequest`]: struct.NewWindowRequest.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum NewWindowPolicy {
+    Open(WindowConfig),
+    Deny,
+    OpenInSystemBrowser,
+}
+
+/// A handler registered with [`WebViewBuilder::new_window_handler()`].
+///
+/// [`WebViewBuilder::new_window_handler()`]: struct.WebViewBuilder.html#method.new_window_handler
+pub type NewWindowHandler<'a> = Box<dyn FnMut(&NewWindowRequest) -> NewWindowPolicy + 'a>;
+
+#[test]
+fn test_modifiers() {
+    let modifiers = Modifiers::from_raw(WEBVIEW_MODIFIER_CONTROL | WEBVIEW_MODIFIER_META);
+    assert!(!modifiers.shift);
+    assert!(modifiers.control);
+    assert!(!modifiers.alt);
+    assert!(modifiers.meta);
+}
+
+#[test]
+fn test_window_features() {
+    let features = WindowFeatures::from_raw(&CWindowFeatures {
+        has_position: 0,
+        x: 0,
+        y: 0,
+        width: 400,
+        height: 300,
+        resizable: 1,
+        fullscreen: 0,
+    });
+    assert_eq!(features.x, None);
+    assert_eq!(features.width, Some(400));
+    assert_eq!(features.height, Some(300));
+    assert!(features.resizable);
+    assert!(!features.fullscreen);
+
+    // Windows may be placed left of or above the primary monitor.
+    let features = WindowFeatures::from_raw(&CWindowFeatures {
+        has_position: 1,
+        x: -1,
+        y: 20,
+        width: 0,
+        height: 0,
+        resizable: 0,
+        fullscreen: 0,
+    });
+    assert_eq!(features.x, Some(-1));
+    assert_eq!(features.y, Some(20));
+    assert_eq!(features.width, None);
+}
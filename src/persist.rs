@@ -0,0 +1,232 @@
+use ffi::{CRect, CWebView};
+use serde_json::{self, Value};
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use timer::TimerId;
+use window::WindowEvent;
+
+/// The geometry of a window, saved across launches by
+/// `WebViewBuilder::persist_window_state()`.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct WindowState {
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) maximized: bool,
+    pub(crate) fullscreen: bool,
+    pub(crate) zoom_level: f64,
+}
+
+impl WindowState {
+    /// Moves the window onto the monitor it overlaps the most, or the first one if it is on none
+    /// of them, and shrinks it if it doesn't fit. Monitors that were unplugged since the state
+    /// was saved would otherwise leave it off-screen.
+    pub(crate) fn clamp_to(&mut self, monitors: &[CRect]) {
+        let overlap = |monitor: &CRect| {
+            let width =
+                (self.x + self.width).min(monitor.x + monitor.width) - self.x.max(monitor.x);
+            let height =
+                (self.y + self.height).min(monitor.y + monitor.height) - self.y.max(monitor.y);
+            i64::from(width.max(0)) * i64::from(height.max(0))
+        };
+        let monitor = match monitors
+            .iter()
+            .filter(|monitor| overlap(monitor) > 0)
+            .max_by_key(|monitor| overlap(monitor))
+            .or_else(|| monitors.first())
+        {
+            Some(monitor) => *monitor,
+            None => return,
+        };
+        self.width = self.width.min(monitor.width);
+        self.height = self.height.min(monitor.height);
+        self.x = self
+            .x
+            .max(monitor.x)
+            .min(monitor.x + monitor.width - self.width);
+        self.y = self
+            .y
+            .max(monitor.y)
+            .min(monitor.y + monitor.height - self.height);
+    }
+}
+
+/// Keeps the state of a window up to date and saves it in a file shared by all windows, under
+/// the window's key.
+pub(crate) struct Persist {
+    // Windows opened by the page share the handlers of this one, but not its state.
+    pub(crate) webview: *mut CWebView,
+    pub(crate) key: String,
+    pub(crate) path: PathBuf,
+    pub(crate) state: WindowState,
+    // Saves the state once changes have settled.
+    pub(crate) save_timer: Option<TimerId>,
+}
+
+impl Persist {
+    /// Returns the state saved under `key`, or `None` if there is none or the file can't be
+    /// read.
+    pub(crate) fn load(&self) -> Option<WindowState> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        let mut states: BTreeMap<String, Value> = serde_json::from_str(&contents).ok()?;
+        serde_json::from_value(states.remove(&self.key)?).ok()
+    }
+
+    /// Saves the state, keeping those of the other windows in the file. Errors are ignored, as
+    /// the state is saved again when the window is closed.
+    pub(crate) fn save(&self) {
+        let mut states: BTreeMap<String, Value> = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        if let Ok(state) = serde_json::to_value(self.state) {
+            states.insert(self.key.clone(), state);
+        }
+        if let Ok(contents) = serde_json::to_string_pretty(&states) {
+            let _ = fs::write(&self.path, contents);
+        }
+    }
+
+    /// Records a change of the window. The size and position are those of the restored window,
+    /// so they are kept while it is maximized or fullscreen.
+    pub(crate) fn update(&mut self, event: WindowEvent) {
+        let state = &mut self.state;
+        let restored = !state.maximized && !state.fullscreen;
+        match event {
+            WindowEvent::Resized { width, height } if restored => {
+                state.width = width;
+                state.height = height;
+            }
+            WindowEvent::Moved { x, y } if restored => {
+                state.x = x;
+                state.y = y;
+            }
+            WindowEvent::Maximized => state.maximized = true,
+            WindowEvent::Restored => state.maximized = false,
+            WindowEvent::FullscreenEntered => state.fullscreen = true,
+            WindowEvent::FullscreenLeft => state.fullscreen = false,
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn test_clamp_to() {
+    let monitors = [
+        CRect {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1040,
+        },
+        CRect {
+            x: 1920,
+            y: 0,
+            width: 1280,
+            height: 1024,
+        },
+    ];
+    let state = |x, y, width, height| WindowState {
+        x,
+        y,
+        width,
+        height,
+        maximized: false,
+        fullscreen: false,
+        zoom_level: 1.0,
+    };
+    let clamped = |mut state: WindowState, monitors: &[CRect]| {
+        state.clamp_to(monitors);
+        (state.x, state.y, state.width, state.height)
+    };
+
+    // Fully visible windows stay where they are, also on the second monitor.
+    assert_eq!(
+        clamped(state(100, 100, 800, 600), &monitors),
+        (100, 100, 800, 600)
+    );
+    assert_eq!(
+        clamped(state(2000, 50, 800, 600), &monitors),
+        (2000, 50, 800, 600)
+    );
+    // Windows partly off-screen are moved onto the monitor they overlap the most.
+    assert_eq!(
+        clamped(state(2800, 900, 800, 600), &monitors),
+        (2400, 424, 800, 600)
+    );
+    // Windows on an unplugged monitor are moved to the first one.
+    assert_eq!(
+        clamped(state(3500, 200, 800, 600), &monitors[..1]),
+        (1120, 200, 800, 600)
+    );
+    // Windows too large for the monitor are shrunk.
+    assert_eq!(
+        clamped(state(-50, -50, 2560, 1440), &monitors),
+        (0, 0, 1920, 1040)
+    );
+    // Without known monitors, the state is restored as it is.
+    assert_eq!(
+        clamped(state(-5000, 0, 800, 600), &[]),
+        (-5000, 0, 800, 600)
+    );
+}
+
+#[test]
+fn test_update() {
+    let mut persist = Persist {
+        webview: std::ptr::null_mut(),
+        key: "main".to_string(),
+        path: PathBuf::new(),
+        state: WindowState {
+            x: 10,
+            y: 20,
+            width: 800,
+            height: 600,
+            maximized: false,
+            fullscreen: false,
+            zoom_level: 1.0,
+        },
+        save_timer: None,
+    };
+    persist.update(WindowEvent::Moved { x: 30, y: 40 });
+    persist.update(WindowEvent::Maximized);
+    persist.update(WindowEvent::Resized {
+        width: 1920,
+        height: 1040,
+    });
+    persist.update(WindowEvent::Moved { x: 0, y: 0 });
+    assert_eq!((persist.state.x, persist.state.y), (30, 40));
+    assert_eq!((persist.state.width, persist.state.height), (800, 600));
+    assert!(persist.state.maximized);
+    persist.update(WindowEvent::Restored);
+    assert!(!persist.state.maximized);
+}
+
+#[test]
+fn test_save_and_load() {
+    let path = std::env::temp_dir().join(format!("web-view-state-{}.json", std::process::id()));
+    let state = WindowState {
+        x: 10,
+        y: 20,
+        width: 800,
+        height: 600,
+        maximized: true,
+        fullscreen: false,
+        zoom_level: 1.25,
+    };
+    let persist = |key: &str| Persist {
+        webview: std::ptr::null_mut(),
+        key: key.to_string(),
+        path: path.clone(),
+        state,
+        save_timer: None,
+    };
+    assert_eq!(persist("main").load(), None);
+    persist("main").save();
+    let mut settings = persist("settings");
+    settings.state.width = 400;
+    settings.save();
+    assert_eq!(persist("main").load(), Some(state));
+    assert_eq!(persist("settings").load(), Some(settings.state));
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,322 @@
+use escape::{escape, escape_json};
+use ffi::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{self, Value};
+use std::{
+    ffi::CString,
+    marker::PhantomData,
+    os::raw::c_void,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock, Weak,
+    },
+};
+use {is_closed, CustomError, Error, WVResult, WebView};
+
+/// JavaScript defining the `rpc` object used to call named handlers registered with
+/// [`WebViewBuilder::rpc()`].
+///
+/// `rpc.call(name, args)` returns a promise which is settled by `window.rpc._settle` once Rust
+/// has handled the call with the same id. Ids start with a nonce of the page, so a late response
+/// to a page which has been navigated away from doesn't settle a call of the next one, even where
+/// navigations aren't reported to Rust.
+///
+/// [`WebViewBuilder::rpc()`]: struct.WebViewBuilder.html#method.rpc
+pub(crate) const RPC_BRIDGE: &str = r#"
+(function() {
+    if (window.rpc) {
+        return;
+    }
+    var pending = {};
+    var page = Math.random().toString(36).slice(2) + Date.now().toString(36);
+    var nextId = 0;
+    window.rpc = {
+        call: function(method, params) {
+            var id = page + ':' + ++nextId;
+            return new Promise(function(resolve, reject) {
+                pending[id] = { resolve: resolve, reject: reject };
+                window.external.invoke(JSON.stringify({
+                    __webview__: 'rpc',
+                    id: id,
+                    method: method,
+                    params: params === undefined ? null : params
+                }));
+            });
+        },
+        _settle: function(id, error, result) {
+            var call = pending[id];
+            if (!call) {
+                return;
+            }
+            delete pending[id];
+            if (error === null) {
+                call.resolve(result);
+            } else {
+                call.reject(new Error(error));
+            }
+        }
+    };
+})();
+"#;
+
+/// Every message sent by the crate's own JavaScript starts with this, which lets us tell them
+/// apart from user messages without parsing the latter.
+const MESSAGE_PREFIX: &str = "{\"__webview__\":";
+
+/// A message sent by the crate's own JavaScript through `window.external.invoke`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "__webview__", rename_all = "lowercase")]
+pub(crate) enum Message {
+    Rpc {
+        id: String,
+        method: String,
+        params: Value,
+    },
+}
+
+impl Message {
+    /// Parses `arg` if it is a message sent by the crate's own JavaScript.
+    pub(crate) fn parse(arg: &str) -> Option<Message> {
+        if arg.starts_with(MESSAGE_PREFIX) {
+            serde_json::from_str(arg).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// A type-erased handler registered with [`WebViewBuilder::rpc()`] or
+/// [`WebViewBuilder::rpc_async()`].
+///
+/// [`WebViewBuilder::rpc()`]: struct.WebViewBuilder.html#method.rpc
+/// [`WebViewBuilder::rpc_async()`]: struct.WebViewBuilder.html#method.rpc_async
+pub type RpcHandler<'a, T> =
+    Box<dyn FnMut(&mut WebView<T>, Value, Responder<Value>) -> WVResult + 'a>;
+
+pub(crate) fn rpc_handler<'a, T, A, R, F>(mut handler: F) -> RpcHandler<'a, T>
+where
+    A: DeserializeOwned,
+    R: Serialize,
+    F: FnMut(&mut WebView<T>, A) -> WVResult<R> + 'a,
+{
+    Box::new(move |webview, params, responder| {
+        let result = serde_json::from_value(params)
+            .map_err(Error::from)
+            .and_then(|args| handler(webview, args));
+        responder.cast().respond(result)
+    })
+}
+
+pub(crate) fn rpc_async_handler<'a, T, A, R, F>(mut handler: F) -> RpcHandler<'a, T>
+where
+    A: DeserializeOwned,
+    R: Serialize,
+    F: FnMut(&mut WebView<T>, A, Responder<R>) + 'a,
+{
+    Box::new(move |webview, params, responder| {
+        match serde_json::from_value(params) {
+            Ok(args) => handler(webview, args, responder.cast()),
+            Err(e) => return responder.respond(Err(e.into())),
+        }
+        Ok(())
+    })
+}
+
+/// Runs the handler registered for `method`, which settles the promise of call `id`.
+pub(crate) fn handle_call<T>(
+    webview: &mut WebView<T>,
+    id: String,
+    method: &str,
+    params: Value,
+) -> WVResult {
+    let responder = Responder::new(webview, id);
+    unsafe {
+        match (*webview.user_data_wrapper_ptr())
+            .rpc_handlers
+            .get_mut(method)
+        {
+            Some(handler) => handler(webview, params, responder),
+            None => responder.reject(format!("Unknown RPC method: {}", method)),
+        }
+    }
+}
+
+/// Settles the promise returned by an `rpc.call` on the page.
+///
+/// Received by handlers registered with [`WebViewBuilder::rpc_async()`]. A `Responder` can be
+/// sent to another thread, so the call can be completed later without blocking the `WebView`.
+///
+/// The call is cancelled when the page navigates away or the [`WebView`] is dropped. Responding
+/// to a cancelled call does nothing, and long-running work can check [`is_cancelled()`] to stop
+/// early. If a `Responder` is dropped without responding, the promise is rejected.
+///
+/// Navigations are only noticed on GTK. Elsewhere, [`is_cancelled()`] only turns `true` once the
+/// [`WebView`] is dropped, and responses to a previous page are ignored by the current one.
+///
+/// [`WebViewBuilder::rpc_async()`]: struct.WebViewBuilder.html#method.rpc_async
+/// [`WebView`]: struct.WebView.html
+/// [`is_cancelled()`]: struct.Responder.html#method.is_cancelled
+pub struct Responder<R> {
+    inner: *mut CWebView,
+    live: Weak<RwLock<bool>>,
+    page: Arc<AtomicUsize>,
+    call_page: usize,
+    id: String,
+    settled: bool,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> Responder<R> {
+    fn new<T>(webview: &WebView<T>, id: String) -> Responder<R> {
+        let page = webview
+            .view()
+            .map_or_else(Default::default, |view| view.page.clone());
+        Responder {
+            inner: webview.inner.unwrap(),
+            live: webview.live(),
+            call_page: page.load(Ordering::SeqCst),
+            page,
+            id,
+            settled: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn cast<S>(mut self) -> Responder<S> {
+        self.settled = true;
+        Responder {
+            inner: self.inner,
+            live: self.live.clone(),
+            page: self.page.clone(),
+            call_page: self.call_page,
+            id: self.id.clone(),
+            settled: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the page that made the call has navigated away, or its window has been
+    /// closed or the [`WebView`] dropped. Navigations are only noticed on GTK.
+    ///
+    /// [`WebView`]: struct.WebView.html
+    pub fn is_cancelled(&self) -> bool {
+        if self.page.load(Ordering::SeqCst) != self.call_page {
+            return true;
+        }
+        match self.live.upgrade() {
+            Some(live) => match live.read() {
+                Ok(live) => !*live,
+                Err(_) => true,
+            },
+            None => true,
+        }
+    }
+
+    /// Rejects the promise with the given error message.
+    pub fn reject<E: CustomError>(mut self, error: E) -> WVResult {
+        self.settle(Err(Error::custom(error)))
+    }
+
+    fn settle(&mut self, result: WVResult<Value>) -> WVResult {
+        self.settled = true;
+
+        if self.is_cancelled() {
+            return Ok(());
+        }
+
+        let settlement = Box::new(Settlement {
+            js: CString::new(settle_js(&self.id, &result)?)?,
+            page: self.page.clone(),
+            call_page: self.call_page,
+        });
+
+        // Abort if WebView has been dropped. Otherwise, keep it alive until the settlement has
+        // been dispatched.
+        let mutex = self.live.upgrade().ok_or(Error::Dispatch)?;
+        let live = mutex.read().map_err(|_| Error::Dispatch)?;
+        if !*live {
+            return Err(Error::Dispatch);
+        }
+
+        unsafe {
+            webview_dispatch(
+                self.inner,
+                Some(ffi_settle_handler),
+                Box::into_raw(settlement) as _,
+            )
+        }
+        Ok(())
+    }
+}
+
+impl<R: Serialize> Responder<R> {
+    /// Resolves the promise with `value`.
+    pub fn resolve(self, value: R) -> WVResult {
+        self.respond(Ok(value))
+    }
+
+    /// Resolves the promise with the `Ok` value, or rejects it with the message of the `Err`.
+    pub fn respond(mut self, result: WVResult<R>) -> WVResult {
+        let result = result.and_then(|value| Ok(serde_json::to_value(value)?));
+        self.settle(result)
+    }
+}
+
+impl<R> Drop for Responder<R> {
+    fn drop(&mut self) {
+        if !self.settled {
+            let _ = self.settle(Err(Error::custom(
+                "The call was dropped without a response.",
+            )));
+        }
+    }
+}
+
+unsafe impl<R> Send for Responder<R> {}
+
+struct Settlement {
+    js: CString,
+    page: Arc<AtomicUsize>,
+    call_page: usize,
+}
+
+extern "C" fn ffi_settle_handler(webview: *mut CWebView, arg: *mut c_void) {
+    unsafe {
+        let settlement = Box::<Settlement>::from_raw(arg as _);
+        // The page may have navigated away or its window closed while the settlement was queued.
+        if settlement.page.load(Ordering::SeqCst) == settlement.call_page && !is_closed(webview) {
+            webview_eval(webview, settlement.js.as_ptr());
+        }
+    }
+}
+
+/// Returns JavaScript settling the promise of call `id` with `result`.
+pub(crate) fn settle_js(id: &str, result: &WVResult<Value>) -> WVResult<String> {
+    Ok(match result {
+        Ok(value) => format!(
+            "window.rpc._settle({}, null, {})",
+            escape(id),
+            escape_json(value)?
+        ),
+        Err(e) => format!(
+            "window.rpc._settle({}, {})",
+            escape(id),
+            escape(&e.to_string())
+        ),
+    })
+}
+
+#[test]
+fn test_parse() {
+    let arg = r#"{"__webview__":"rpc","id":"k3x9:1","method":"add","params":[1,2]}"#;
+    match Message::parse(arg) {
+        Some(Message::Rpc { id, method, params }) => {
+            assert!(id == "k3x9:1");
+            assert!(method == "add");
+            assert!(params == serde_json::json!([1, 2]));
+        }
+        _ => panic!("rpc message not recognized"),
+    }
+    assert!(Message::parse(r#"{"cmd":"add"}"#).is_none());
+    assert!(Message::parse("exit").is_none());
+}
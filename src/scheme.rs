@@ -0,0 +1,274 @@
+use ffi::*;
+use std::{
+    ffi::CString,
+    io::{self, Read},
+    os::raw::{c_char, c_int, c_void},
+    ptr, slice,
+};
+use urlencoding::decode;
+use {Error, WVResult};
+
+/// A request for a URI with a scheme registered with [`WebViewBuilder::register_scheme()`].
+///
+/// [`WebViewBuilder::register_scheme()`]: struct.WebViewBuilder.html#method.register_scheme
+#[derive(Debug, Clone)]
+pub struct SchemeRequest {
+    /// The full URI, e.g. `app://localhost/index.html?page=2`.
+    pub uri: String,
+    /// The percent-decoded path of the URI, without query and fragment, e.g. `/index.html`.
+    pub path: String,
+    /// The HTTP method. Always `GET` on GTK unless the `webkit2gtk-2-36` feature is enabled.
+    pub method: String,
+    /// The request headers. Always empty on GTK unless the `webkit2gtk-2-36` feature is
+    /// enabled.
+    pub headers: Vec<(String, String)>,
+    /// The body of e.g. a `POST` request made with `fetch`. Always empty on GTK unless the
+    /// `webkit2gtk-2-40` feature is enabled.
+    pub body: Vec<u8>,
+}
+
+impl SchemeRequest {
+    /// Returns the value of the first header with the given name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// The body of a [`SchemeResponse`].
+///
+/// # Variants
+///
+/// - `Bytes` - The whole body at once.
+/// - `Reader` - A body read in chunks on a background thread as the page consumes it, e.g. a
+///   large file. Contains the length of the body if it is known. All readers share one thread,
+///   so a reader that blocks delays the others.
+///
+/// [`SchemeResponse`]: struct.SchemeResponse.html
+pub enum SchemeBody {
+    Bytes(Vec<u8>),
+    Reader(Box<dyn Read + Send>, Option<u64>),
+}
+
+/// The response to a [`SchemeRequest`].
+///
+/// # Example
+///
+/// ```ignore
+/// SchemeResponse::new("text/html", "<h1>Hello</h1>")
+///     .header("Cache-Control", "no-store")
+/// ```
+///
+/// On GTK, the status and headers are only passed on if the `webkit2gtk-2-36` feature is
+/// enabled. Without it, error statuses fail the request instead, and other statuses than
+/// `200 OK` or any headers make [`WebView::step()`] return [`Error::Unsupported`] after the
+/// response was sent without them.
+///
+/// [`SchemeRequest`]: struct.SchemeRequest.html
+/// [`WebView::step()`]: struct.WebView.html#method.step
+/// [`Error::Unsupported`]: enum.Error.html#variant.Unsupported
+pub struct SchemeResponse {
+    pub status: u16,
+    pub mime_type: String,
+    pub headers: Vec<(String, String)>,
+    pub body: SchemeBody,
+}
+
+impl SchemeResponse {
+    /// Creates a `200 OK` response with the given MIME type and body.
+    pub fn new<B: Into<Vec<u8>>>(mime_type: &str, body: B) -> Self {
+        SchemeResponse {
+            status: 200,
+            mime_type: mime_type.to_string(),
+            headers: Vec::new(),
+            body: SchemeBody::Bytes(body.into()),
+        }
+    }
+
+    /// Creates a `200 OK` response with the given MIME type, streaming its body from `reader`.
+    ///
+    /// If reading the body fails, the page gets a network error instead of a truncated body.
+    pub fn from_reader<R: Read + Send + 'static>(
+        mime_type: &str,
+        reader: R,
+        length: Option<u64>,
+    ) -> Self {
+        SchemeResponse {
+            status: 200,
+            mime_type: mime_type.to_string(),
+            headers: Vec::new(),
+            body: SchemeBody::Reader(Box::new(reader), length),
+        }
+    }
+
+    /// Creates a `404 Not Found` response.
+    pub fn not_found() -> Self {
+        SchemeResponse::new("text/plain", "Not Found").status(404)
+    }
+
+    /// Sets the status code.
+    pub fn status(mut self, status: u16) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Adds a header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// A handler registered with [`WebViewBuilder::register_scheme()`].
+///
+/// [`WebViewBuilder::register_scheme()`]: struct.WebViewBuilder.html#method.register_scheme
+pub type SchemeHandler<'a> = Box<dyn FnMut(SchemeRequest) -> SchemeResponse + 'a>;
+
+/// Extracts the percent-decoded path from a URI, e.g. `/index.html` from
+/// `app://localhost/index.html?page=2`.
+fn path_from_uri(uri: &str) -> String {
+    let rest = match uri.find(':') {
+        Some(colon) => &uri[colon + 1..],
+        None => uri,
+    };
+    let rest = rest.split(&['?', '#'][..]).next().unwrap_or_default();
+    let path = match rest.strip_prefix("//") {
+        Some(authority_and_path) => match authority_and_path.find('/') {
+            Some(slash) => &authority_and_path[slash..],
+            None => "",
+        },
+        None => rest,
+    };
+    let path = decode(path).unwrap_or_else(|_| path.to_string());
+    if path.starts_with('/') {
+        path
+    } else {
+        format!("/{}", path)
+    }
+}
+
+extern "C" fn header_cb(arg: *mut c_void, name: *const c_char, value: *const c_char) {
+    unsafe {
+        let headers = &mut *(arg as *mut Vec<(String, String)>);
+        if let (Some(name), Some(value)) =
+            (super::string_from_ptr(name), super::string_from_ptr(value))
+        {
+            headers.push((name, value));
+        }
+    }
+}
+
+extern "C" fn body_cb(arg: *mut c_void, data: *const c_void, len: usize) {
+    unsafe {
+        let body = &mut *(arg as *mut Vec<u8>);
+        body.extend_from_slice(slice::from_raw_parts(data as *const u8, len));
+    }
+}
+
+pub(crate) unsafe fn read_request(request: *mut CSchemeRequest) -> SchemeRequest {
+    let uri = super::string_from_ptr(webview_scheme_request_get_uri(request)).unwrap_or_default();
+    let method = super::string_from_ptr(webview_scheme_request_get_method(request))
+        .unwrap_or_else(|| "GET".to_string());
+    let mut headers = Vec::new();
+    webview_scheme_request_get_headers(
+        request,
+        header_cb,
+        &mut headers as *mut Vec<(String, String)> as *mut c_void,
+    );
+    let mut body = Vec::new();
+    webview_scheme_request_get_body(request, body_cb, &mut body as *mut Vec<u8> as *mut c_void);
+    SchemeRequest {
+        path: path_from_uri(&uri),
+        uri,
+        method,
+        headers,
+        body,
+    }
+}
+
+extern "C" fn read_fn(arg: *mut c_void, buf: *mut c_char, len: usize) -> i64 {
+    unsafe {
+        let reader = &mut *(arg as *mut Box<dyn Read + Send>);
+        loop {
+            match reader.read(slice::from_raw_parts_mut(buf as *mut u8, len)) {
+                Ok(n) => return n as i64,
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => return -1,
+            }
+        }
+    }
+}
+
+extern "C" fn close_fn(arg: *mut c_void) {
+    unsafe {
+        let _ = Box::from_raw(arg as *mut Box<dyn Read + Send>);
+    }
+}
+
+pub(crate) unsafe fn finish_request(
+    request: *mut CSchemeRequest,
+    response: SchemeResponse,
+) -> WVResult {
+    // Interior nul bytes can't be passed on, so drop the offending parts.
+    let mime_type = CString::new(response.mime_type)
+        .unwrap_or_else(|_| CString::new("application/octet-stream").unwrap());
+    let headers: Vec<(CString, CString)> = response
+        .headers
+        .into_iter()
+        .filter_map(|(name, value)| Some((CString::new(name).ok()?, CString::new(value).ok()?)))
+        .collect();
+    let names: Vec<*const c_char> = headers.iter().map(|(name, _)| name.as_ptr()).collect();
+    let values: Vec<*const c_char> = headers.iter().map(|(_, value)| value.as_ptr()).collect();
+
+    let ret = match response.body {
+        SchemeBody::Bytes(body) => webview_scheme_request_finish(
+            request,
+            response.status as c_int,
+            mime_type.as_ptr(),
+            names.as_ptr(),
+            values.as_ptr(),
+            headers.len() as c_int,
+            if body.is_empty() {
+                ptr::null()
+            } else {
+                body.as_ptr() as *const c_void
+            },
+            body.len(),
+        ),
+        SchemeBody::Reader(reader, length) => webview_scheme_request_finish_stream(
+            request,
+            response.status as c_int,
+            mime_type.as_ptr(),
+            names.as_ptr(),
+            values.as_ptr(),
+            headers.len() as c_int,
+            length.map_or(-1, |length| length as i64),
+            read_fn,
+            close_fn,
+            Box::into_raw(Box::new(reader)) as *mut c_void,
+        ),
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(Error::Unsupported(
+            "Passing on the status and headers of a scheme response",
+        ))
+    }
+}
+
+#[test]
+fn test_path_from_uri() {
+    assert_eq!(path_from_uri("app://localhost/index.html"), "/index.html");
+    assert_eq!(path_from_uri("app://localhost"), "/");
+    assert_eq!(
+        path_from_uri("app:///css/main.css?v=2#top"),
+        "/css/main.css"
+    );
+    assert_eq!(
+        path_from_uri("app:images/my%20cat.png"),
+        "/images/my cat.png"
+    );
+}
@@ -0,0 +1,34 @@
+/// When a user script runs while a page loads.
+///
+/// # Variants
+///
+/// - `DocumentStart` - Before any of the page's own scripts, when the document is still empty.
+/// - `DocumentEnd` - Once the document has been parsed, before subresources such as images have
+///   finished loading.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum InjectAt {
+    DocumentStart,
+    DocumentEnd,
+}
+
+/// Which frames of a page a user script runs in.
+///
+/// # Variants
+///
+/// - `TopOnly` - Only the top-level document.
+/// - `All` - The top-level document and every `<iframe>` in it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Frames {
+    TopOnly,
+    All,
+}
+
+/// A script injected into every page loaded by a [`WebView`].
+///
+/// [`WebView`]: struct.WebView.html
+#[derive(Debug, Copy, Clone)]
+pub struct UserScript<'a> {
+    pub source: &'a str,
+    pub inject_at: InjectAt,
+    pub frames: Frames,
+}
@@ -0,0 +1,22 @@
+use std::os::raw::c_int;
+
+/// The cascade origin of a user style sheet.
+///
+/// # Variants
+///
+/// - `User` - Like a style sheet configured by the user of a browser. The page's own styles take
+///   precedence, except for `!important` declarations.
+/// - `Author` - Like a style sheet of the page itself, cascading with its other styles.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StyleLevel {
+    User,
+    Author,
+}
+
+/// A style sheet added with [`WebView::add_style_sheet()`], which can be passed to
+/// [`WebView::remove_style_sheet()`].
+///
+/// [`WebView::add_style_sheet()`]: struct.WebView.html#method.add_style_sheet
+/// [`WebView::remove_style_sheet()`]: struct.WebView.html#method.remove_style_sheet
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct StyleSheet(pub(crate) c_int);
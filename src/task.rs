@@ -0,0 +1,116 @@
+use ffi::*;
+use std::{
+    cell::Cell,
+    future::Future,
+    marker::PhantomData,
+    os::raw::c_void,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock, Weak,
+    },
+    task::Wake,
+};
+use {Error, WVResult, WebView};
+
+pub(crate) type LocalFuture<'a> = Pin<Box<dyn Future<Output = WVResult> + 'a>>;
+
+thread_local! {
+    // The webview whose task is being polled, `LocalHandle::with()` is only allowed in there.
+    static POLLING: Cell<*mut CWebView> = const { Cell::new(std::ptr::null_mut()) };
+}
+
+/// Marks `webview` as being polled while `f` runs.
+pub(crate) fn polling<R, F: FnOnce() -> R>(webview: *mut CWebView, f: F) -> R {
+    let previous = POLLING.with(|polling| polling.replace(webview));
+    let result = f();
+    POLLING.with(|polling| polling.set(previous));
+    result
+}
+
+/// Schedules polling a task when its future is woken up, from any thread.
+pub(crate) struct TaskWaker {
+    pub(crate) id: usize,
+    pub(crate) inner: *mut CWebView,
+    pub(crate) live: Weak<RwLock<bool>>,
+    pub(crate) poll: extern "C" fn(webview: *mut CWebView, arg: *mut c_void),
+    pub(crate) scheduled: AtomicBool,
+}
+
+unsafe impl Send for TaskWaker {}
+unsafe impl Sync for TaskWaker {}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        if self.scheduled.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // The task is dropped along with the WebView, so there is nothing to poll anymore.
+        if let Some(live) = self.live.upgrade() {
+            match live.read() {
+                Ok(live) if *live => unsafe {
+                    webview_dispatch(self.inner, Some(self.poll), self.id as *mut c_void)
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Gives a future spawned with [`WebView::spawn_local()`] access to its `WebView`.
+///
+/// [`WebView::spawn_local()`]: struct.WebView.html#method.spawn_local
+pub struct LocalHandle<T> {
+    pub(crate) inner: *mut CWebView,
+    pub(crate) live: Weak<RwLock<bool>>,
+    pub(crate) _phantom: PhantomData<T>,
+}
+
+impl<T> Clone for LocalHandle<T> {
+    fn clone(&self) -> Self {
+        LocalHandle {
+            inner: self.inner,
+            live: self.live.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T> LocalHandle<T> {
+    /// Calls `f` with the `WebView`, e.g. between two awaits.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Dispatch`] if the `WebView` has been dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if not called from a future spawned on the `WebView`, which is the only place
+    /// where the `WebView` isn't borrowed elsewhere.
+    ///
+    /// [`Error::Dispatch`]: enum.Error.html#variant.Dispatch
+    pub fn with<F, R>(&self, f: F) -> WVResult<R>
+    where
+        F: FnOnce(&mut WebView<T>) -> R,
+    {
+        let live = self.live.upgrade().ok_or(Error::Dispatch)?;
+        if !*live.read().map_err(|_| Error::Dispatch)? {
+            return Err(Error::Dispatch);
+        }
+        assert!(
+            POLLING.with(|polling| polling.get() == self.inner),
+            "LocalHandle::with() must be called from a future spawned on its WebView"
+        );
+        unsafe {
+            let mut webview = WebView::<T>::from_ptr(self.inner);
+            let result = f(&mut webview);
+            // Do not clean up the webview on drop of the temporary WebView
+            webview.inner = None;
+            Ok(result)
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::os::raw::c_int;
+use {WVResult, WebView};
+
+/// Identifies a timer started with [`WebView::set_timeout()`] or [`WebView::set_interval()`].
+///
+/// [`WebView::set_timeout()`]: struct.WebView.html#method.set_timeout
+/// [`WebView::set_interval()`]: struct.WebView.html#method.set_interval
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TimerId(pub(crate) c_int);
+
+type TimerCallback<'a, T> = Box<dyn FnMut(&mut WebView<T>) -> WVResult + 'a>;
+
+pub(crate) struct Timer<'a, T: 'a> {
+    pub(crate) callback: TimerCallback<'a, T>,
+    pub(crate) repeat: bool,
+}
@@ -0,0 +1,113 @@
+use ffi::*;
+use std::os::raw::c_int;
+use {WVResult, WebView};
+
+/// A change of the window, passed to the handler registered with
+/// [`WebViewBuilder::on_window_event()`].
+///
+/// # Variants
+///
+/// - `Resized` - The window was resized. Contains its new size.
+/// - `Moved` - The window was moved. Contains the new position of its top left corner.
+/// - `Focused` - The window gained the keyboard focus.
+/// - `Unfocused` - The window lost the keyboard focus.
+/// - `Minimized` - The window was minimized.
+/// - `Maximized` - The window was maximized.
+/// - `Restored` - The window is neither minimized nor maximized anymore.
+/// - `FullscreenEntered` - The window became fullscreen.
+/// - `FullscreenLeft` - The window isn't fullscreen anymore.
+///
+/// [`WebViewBuilder::on_window_event()`]: struct.WebViewBuilder.html#method.on_window_event
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum WindowEvent {
+    Resized { width: i32, height: i32 },
+    Moved { x: i32, y: i32 },
+    Focused,
+    Unfocused,
+    Minimized,
+    Maximized,
+    Restored,
+    FullscreenEntered,
+    FullscreenLeft,
+}
+
+impl WindowEvent {
+    pub(crate) fn from_raw(event: c_int, a: c_int, b: c_int) -> Option<WindowEvent> {
+        Some(match event {
+            WEBVIEW_WINDOW_RESIZED => WindowEvent::Resized {
+                width: a,
+                height: b,
+            },
+            WEBVIEW_WINDOW_MOVED => WindowEvent::Moved { x: a, y: b },
+            WEBVIEW_WINDOW_FOCUSED => WindowEvent::Focused,
+            WEBVIEW_WINDOW_UNFOCUSED => WindowEvent::Unfocused,
+            WEBVIEW_WINDOW_MINIMIZED => WindowEvent::Minimized,
+            WEBVIEW_WINDOW_MAXIMIZED => WindowEvent::Maximized,
+            WEBVIEW_WINDOW_RESTORED => WindowEvent::Restored,
+            WEBVIEW_WINDOW_FULLSCREEN_ENTERED => WindowEvent::FullscreenEntered,
+            WEBVIEW_WINDOW_FULLSCREEN_LEFT => WindowEvent::FullscreenLeft,
+            _ => return None,
+        })
+    }
+}
+
+/// What a window is used for, which the window manager may decorate and place it by.
+///
+/// # Variants
+///
+/// - `Normal` - A regular top-level window.
+/// - `Utility` - A small persistent window, like a palette or toolbox.
+/// - `Dialog` - A transient window, like an error message or a settings dialog.
+/// - `Splash` - A splash screen shown while the application starts.
+/// - `Dock` - A dock or panel, usually kept above other windows.
+///
+/// Only applied on Linux so far.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum WindowType {
+    Normal,
+    Utility,
+    Dialog,
+    Splash,
+    Dock,
+}
+
+impl WindowType {
+    pub(crate) fn to_raw(self) -> c_int {
+        match self {
+            WindowType::Normal => WEBVIEW_WINDOW_TYPE_NORMAL,
+            WindowType::Utility => WEBVIEW_WINDOW_TYPE_UTILITY,
+            WindowType::Dialog => WEBVIEW_WINDOW_TYPE_DIALOG,
+            WindowType::Splash => WEBVIEW_WINDOW_TYPE_SPLASH,
+            WindowType::Dock => WEBVIEW_WINDOW_TYPE_DOCK,
+        }
+    }
+}
+
+/// A handler registered with [`WebViewBuilder::on_window_event()`].
+///
+/// [`WebViewBuilder::on_window_event()`]: struct.WebViewBuilder.html#method.on_window_event
+pub type WindowEventHandler<'a, T> = Box<dyn FnMut(&mut WebView<T>, WindowEvent) -> WVResult + 'a>;
+
+/// A handler registered with [`WebViewBuilder::on_close_requested()`].
+///
+/// [`WebViewBuilder::on_close_requested()`]: struct.WebViewBuilder.html#method.on_close_requested
+pub type CloseRequestedHandler<'a, T> = Box<dyn FnMut(&mut WebView<T>) -> WVResult<bool> + 'a>;
+
+#[test]
+fn test_window_event() {
+    assert_eq!(
+        WindowEvent::from_raw(WEBVIEW_WINDOW_RESIZED, 640, 480),
+        Some(WindowEvent::Resized {
+            width: 640,
+            height: 480
+        })
+    );
+    assert_eq!(
+        WindowEvent::from_raw(WEBVIEW_WINDOW_MOVED, -20, 10),
+        Some(WindowEvent::Moved { x: -20, y: 10 })
+    );
+    assert_eq!(
+        WindowEvent::from_raw(WEBVIEW_WINDOW_CLOSE_REQUESTED, 0, 0),
+        None
+    );
+}
@@ -5,35 +5,44 @@ extern crate web_view;
 use web_view::*;
 
 fn main() {
-    let mut webview1 = web_view::builder()
-        .title("Multi window example - Window 1")
-        .content(Content::Url("https://en.m.wikipedia.org/wiki/Main_Page"))
-        .size(800, 600)
-        .resizable(true)
-        .debug(true)
-        .user_data(())
-        .invoke_handler(|_webview, _arg| Ok(()))
-        .build()
-        .unwrap();
-
-    let mut webview2 = web_view::builder()
-        .title("Multi window example - Window 2")
-        .content(Content::Url("https://en.m.wikipedia.org/wiki/Main_Page"))
-        .size(800, 600)
-        .resizable(true)
-        .debug(true)
-        .user_data(())
-        .invoke_handler(|_webview, _arg| Ok(()))
-        .build()
-        .unwrap();
-
-    loop {
-        if webview1.step().is_none() {
-            break;
-        }
+    let mut app = App::new();
+    let handle = app.handle();
+    open_window(&mut app, handle).unwrap();
+    // the app keeps running until the last window is closed
+    app.run().unwrap();
+}
 
-        if webview2.step().is_none() {
-            break;
-        }
-    }
+fn open_window(app: &mut App, handle: AppHandle) -> WVResult {
+    app.open(
+        web_view::builder()
+            .title("Multi window example")
+            .content(Content::Html(HTML))
+            .size(400, 200)
+            .resizable(true)
+            .debug(true)
+            .user_data(())
+            .invoke_handler(move |_webview, arg| {
+                match arg {
+                    // windows can be opened from any thread through the handle
+                    "open" => {
+                        let handle2 = handle.clone();
+                        handle.dispatch(move |app| open_window(app, handle2))?;
+                    }
+                    "quit" => handle.quit()?,
+                    _ => unimplemented!(),
+                }
+                Ok(())
+            }),
+    )?;
+    Ok(())
 }
+
+const HTML: &str = r#"
+<!doctype html>
+<html>
+	<body>
+		<button onclick="external.invoke('open')">open another window</button>
+		<button onclick="external.invoke('quit')">close all windows</button>
+	</body>
+</html>
+"#;
@@ -0,0 +1,81 @@
+//#![windows_subsystem = "windows"]
+
+extern crate web_view;
+
+use web_view::*;
+
+fn main() {
+    web_view::builder()
+        .title("Navigation example")
+        .content(Content::Html(HTML))
+        .size(800, 600)
+        .resizable(true)
+        .debug(true)
+        .user_data(())
+        .invoke_handler(|webview, arg| {
+            match arg {
+                "back" => webview.go_back(),
+                "forward" => webview.go_forward(),
+                "reload" => webview.reload(false),
+                _ => unimplemented!(),
+            }
+            Ok(())
+        })
+        .navigation_handler(|request| {
+            println!("{:?}", request);
+            if request.modifiers.shift {
+                Policy::OpenInNewWindow
+            } else if request.target_frame.is_some() {
+                // Decided on by the new window handler.
+                Policy::Allow
+            } else if request.url.starts_with("about:")
+                || request.url.starts_with("https://en.m.wikipedia.org/")
+            {
+                Policy::Allow
+            } else {
+                Policy::OpenInSystemBrowser
+            }
+        })
+        .new_window_handler(|request| {
+            println!("{:?}", request);
+            if request.url.starts_with("https://en.m.wikipedia.org/") {
+                NewWindowPolicy::Open(WindowConfig {
+                    title: "Wikipedia".to_string(),
+                    ..request.config()
+                })
+            } else {
+                NewWindowPolicy::OpenInSystemBrowser
+            }
+        })
+        .on_load_started(|webview, url| {
+            println!("loading {} ({:.0}%)", url, webview.load_progress() * 100.0);
+            webview.set_title("Loading...")
+        })
+        .on_load_finished(|webview, _url| {
+            let title = webview.title().unwrap_or_default();
+            webview.set_title(&title)
+        })
+        .on_load_failed(|_webview, url, error| {
+            println!("failed to load {}: {}", url, error);
+            Ok(())
+        })
+        .run()
+        .unwrap();
+}
+
+const HTML: &str = r#"
+<!doctype html>
+<html>
+	<body>
+		<button onclick="external.invoke('back')">back</button>
+		<button onclick="external.invoke('forward')">forward</button>
+		<button onclick="external.invoke('reload')">reload</button>
+		<ul>
+			<li><a href="https://en.m.wikipedia.org/wiki/Main_Page">Wikipedia</a> opens in this window (shift-click for a new one)</li>
+			<li><a href="https://en.m.wikipedia.org/wiki/Main_Page" target="_blank">Wikipedia</a> opens in a new window</li>
+			<li><a href="https://www.rust-lang.org/">rust-lang.org</a> opens in your browser, also with <a href="https://www.rust-lang.org/" target="_blank">target="_blank"</a></li>
+			<li><button onclick="window.open('https://en.m.wikipedia.org/wiki/Rust', '', 'width=400,height=600')">window.open</button> opens a small window</li>
+		</ul>
+	</body>
+</html>
+"#;
@@ -0,0 +1,77 @@
+//#![windows_subsystem = "windows"]
+
+#[macro_use]
+extern crate serde_derive;
+extern crate web_view;
+
+use std::{thread, time::Duration};
+use web_view::*;
+
+#[derive(Deserialize)]
+struct Greeting {
+    name: String,
+    times: usize,
+}
+
+fn main() {
+    // The page only talks to Rust through `rpc.call`, so there is no invoke handler.
+    web_view::builder::<_, NoInvokeHandler<_>, _>()
+        .title("RPC example")
+        .content(Content::Html(HTML))
+        .size(800, 600)
+        .resizable(true)
+        .debug(true)
+        .user_data(0)
+        .rpc("add", |_webview, (a, b): (i64, i64)| Ok(a + b))
+        .rpc("greet", |webview, greeting: Greeting| {
+            if greeting.name.is_empty() {
+                return Err(Error::custom("name must not be empty"));
+            }
+            *webview.user_data_mut() += 1;
+            Ok(vec![format!("Hello, {}!", greeting.name); greeting.times])
+        })
+        .rpc("greeted", |webview, ()| Ok(*webview.user_data()))
+        .rpc_async("slow_add", |_webview, (a, b): (i64, i64), responder| {
+            thread::spawn(move || {
+                thread::sleep(Duration::from_secs(1));
+                if !responder.is_cancelled() {
+                    responder.resolve(a + b).unwrap();
+                }
+            });
+        })
+        .run()
+        .unwrap();
+}
+
+const HTML: &str = r#"
+<!doctype html>
+<html>
+	<body>
+		<p><input id="a" type="number" value="1"> + <input id="b" type="number" value="2">
+		<button onclick="add()">=</button> <span id="sum"></span></p>
+		<p><input id="name" placeholder="name"> <button onclick="greet()">greet</button></p>
+		<pre id="output"></pre>
+		<script type="text/javascript">
+			function value(id) {
+				return document.getElementById(id).value;
+			}
+			function show(id, text) {
+				document.getElementById(id).textContent = text;
+			}
+			async function add(method) {
+				show('sum', '...');
+				show('sum', await rpc.call(method || 'add', [Number(value('a')), Number(value('b'))]));
+			}
+			async function greet() {
+				try {
+					var lines = await rpc.call('greet', { name: value('name'), times: 2 });
+					var count = await rpc.call('greeted');
+					show('output', lines.join('\n') + '\n(' + count + ' greetings so far)');
+				} catch (e) {
+					show('output', 'error: ' + e.message);
+				}
+			}
+		</script>
+	</body>
+</html>
+"#;
@@ -0,0 +1,49 @@
+extern crate web_view;
+
+use web_view::*;
+
+fn main() {
+    web_view::builder()
+        .title("Scheme example")
+        .content(Content::Url("app://localhost/index.html"))
+        .size(400, 300)
+        .resizable(true)
+        .debug(true)
+        .user_data(0)
+        .invoke_handler(|_webview, _arg| Ok(()))
+        .register_scheme("app", |request| {
+            match (request.method.as_str(), request.path.as_str()) {
+                ("GET", "/index.html") => SchemeResponse::new("text/html", INDEX_HTML),
+                ("GET", "/style.css") => SchemeResponse::new("text/css", STYLE_CSS),
+                ("POST", "/echo") => SchemeResponse::new("text/plain", request.body)
+                    .header("Cache-Control", "no-store"),
+                _ => SchemeResponse::not_found(),
+            }
+        })
+        .run()
+        .unwrap();
+}
+
+const STYLE_CSS: &str = "body { font-family: sans-serif; } #reply { color: #2a7ae2; }";
+
+const INDEX_HTML: &str = r#"
+<!doctype html>
+<html>
+	<head>
+		<link rel="stylesheet" href="style.css">
+	</head>
+	<body>
+		<p>This page and its style sheet are served from Rust.</p>
+		<input id="message" value="Hello">
+		<button onclick="echo()">echo</button>
+		<p id="reply"></p>
+		<script>
+			function echo() {
+				fetch('/echo', { method: 'POST', body: document.getElementById('message').value })
+					.then(function(response) { return response.text(); })
+					.then(function(text) { document.getElementById('reply').textContent = text; });
+			}
+		</script>
+	</body>
+</html>
+"#;
@@ -0,0 +1,42 @@
+extern crate web_view;
+
+use web_view::*;
+
+fn main() {
+    web_view::builder()
+        .title("Theme example")
+        .content(Content::Html(HTML))
+        .size(400, 200)
+        .resizable(true)
+        .debug(true)
+        .user_data(None)
+        .invoke_handler(|webview, arg| {
+            if let Some(style_sheet) = webview.user_data_mut().take() {
+                webview.remove_style_sheet(style_sheet);
+            }
+            let css = match arg {
+                "dark" => DARK,
+                "light" => LIGHT,
+                _ => unimplemented!(),
+            };
+            *webview.user_data_mut() = Some(webview.add_style_sheet(css, StyleLevel::Author)?);
+            Ok(())
+        })
+        .run()
+        .unwrap();
+}
+
+const LIGHT: &str = "body { background: #fafafa; color: #202020; }";
+const DARK: &str = "body { background: #202020; color: #fafafa; }";
+
+const HTML: &str = r#"
+<!doctype html>
+<html>
+	<body>
+		<p>Themes stay applied when the page is reloaded.</p>
+		<button onclick="external.invoke('light')">light</button>
+		<button onclick="external.invoke('dark')">dark</button>
+		<button onclick="location.reload()">reload</button>
+	</body>
+</html>
+"#;
@@ -2,18 +2,13 @@
 
 extern crate web_view;
 
-use std::{
-    sync::{Arc, Mutex},
-    thread,
-    time::Duration,
-};
+use std::{cell::Cell, time::Duration};
 use web_view::*;
 
 fn main() {
-    let counter = Arc::new(Mutex::new(0));
+    let counter = Cell::new(0);
 
-    let counter_inner = counter.clone();
-    let webview = web_view::builder()
+    let mut webview = web_view::builder()
         .title("Timer example")
         .content(Content::Html(HTML))
         .size(800, 600)
@@ -24,9 +19,8 @@ fn main() {
             match arg {
                 "reset" => {
                     *webview.user_data_mut() += 10;
-                    let mut counter = counter.lock().unwrap();
-                    *counter = 0;
-                    render(webview, *counter)?;
+                    counter.set(0);
+                    render(webview, counter.get())?;
                 }
                 "exit" => {
                     webview.exit();
@@ -38,21 +32,14 @@ fn main() {
         .build()
         .unwrap();
 
-    let handle = webview.handle();
-    thread::spawn(move || loop {
-        {
-            let mut counter = counter_inner.lock().unwrap();
-            *counter += 1;
-            let count = *counter;
-            handle
-                .dispatch(move |webview| {
-                    *webview.user_data_mut() -= 1;
-                    render(webview, count)
-                })
-                .unwrap();
-        }
-        thread::sleep(Duration::from_secs(1));
-    });
+    // Runs on the UI thread, so the state needs no locks.
+    webview
+        .set_interval(Duration::from_secs(1), |webview| {
+            counter.set(counter.get() + 1);
+            *webview.user_data_mut() -= 1;
+            render(webview, counter.get())
+        })
+        .unwrap();
 
     webview.run().unwrap();
 }
@@ -60,7 +47,7 @@ fn main() {
 fn render(webview: &mut WebView<i32>, counter: u32) -> WVResult {
     let user_data = *webview.user_data();
     println!("counter: {}, userdata: {}", counter, user_data);
-    webview.eval(&format!("updateTicks({}, {})", counter, user_data))
+    webview.emit("ticks", &(counter, user_data))
 }
 
 const HTML: &str = r#"
@@ -71,9 +58,11 @@ const HTML: &str = r#"
 		<button onclick="external.invoke('reset')">reset</button>
 		<button onclick="external.invoke('exit')">exit</button>
 		<script type="text/javascript">
-			function updateTicks(n, u) {
-				document.getElementById('ticks').innerHTML = 'ticks ' + n + '<br>' + 'userdata ' + u;
-			}
+			window.addEventListener('webviewready', function() {
+				webview.listen('ticks', function(ticks) {
+					document.getElementById('ticks').innerHTML = 'ticks ' + ticks[0] + '<br>' + 'userdata ' + ticks[1];
+				});
+			});
 		</script>
 	</body>
 </html>
@@ -0,0 +1,54 @@
+//#![windows_subsystem = "windows"]
+
+extern crate web_view;
+
+use web_view::*;
+
+fn main() {
+    web_view::builder()
+        .title("Window events example")
+        .content(Content::Html(HTML))
+        .size(800, 600)
+        .resizable(true)
+        .debug(true)
+        .user_data(false)
+        .invoke_handler(|webview, arg| {
+            match arg {
+                "save" => *webview.user_data_mut() = false,
+                "edit" => *webview.user_data_mut() = true,
+                _ => unimplemented!(),
+            }
+            Ok(())
+        })
+        .on_window_event(|webview, event| {
+            println!("{:?}", event);
+            webview.eval(&format!("log({:?})", format!("{:?}", event)))
+        })
+        .on_close_requested(|webview| {
+            let unsaved_changes = *webview.user_data();
+            if unsaved_changes {
+                webview.eval("log('Save your changes before closing the window.')")?;
+            }
+            Ok(!unsaved_changes)
+        })
+        .run()
+        .unwrap();
+}
+
+const HTML: &str = r#"
+<!doctype html>
+<html>
+	<body>
+		<button onclick="external.invoke('edit')">make changes</button>
+		<button onclick="external.invoke('save')">save</button>
+		<ul id="log"></ul>
+		<script type="text/javascript">
+			function log(message) {
+				var item = document.createElement('li');
+				item.textContent = message;
+				document.getElementById('log').appendChild(item);
+			}
+		</script>
+	</body>
+</html>
+"#;
@@ -0,0 +1,142 @@
+//! Procedural macros for [web-view](https://crates.io/crates/web-view). Use them through the
+//! re-exports in `web_view`.
+
+extern crate proc_macro;
+
+use proc_macro::{Literal, TokenStream, TokenTree};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Embeds a directory into the binary as `web_view::Assets`, to be loaded with
+/// `Content::Assets`.
+///
+/// The path is relative to the directory containing the crate's `Cargo.toml`, and the directory
+/// must exist when the crate is compiled. Pass `compress` as a second argument to store the files
+/// deflated, which needs the `compress-assets` feature of web-view.
+///
+/// ```ignore
+/// .content(Content::Assets(include_assets!("frontend/dist", compress)))
+/// ```
+#[proc_macro]
+pub fn include_assets(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(expanded) => expanded,
+        Err(message) => format!("compile_error!({:?})", message).parse().unwrap(),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream, String> {
+    let mut tokens = input.into_iter();
+    let dir = match tokens.next() {
+        Some(TokenTree::Literal(literal)) => parse_str_literal(&literal.to_string())
+            .ok_or("include_assets! expects a string literal as its first argument")?,
+        _ => return Err("include_assets! expects the path of a directory".to_string()),
+    };
+    let mut compress = false;
+    match tokens.next() {
+        None => {}
+        Some(TokenTree::Punct(ref punct)) if punct.as_char() == ',' => match tokens.next() {
+            None => {}
+            Some(TokenTree::Ident(ref ident)) if ident.to_string() == "compress" => {
+                compress = true;
+                if let Some(TokenTree::Punct(ref punct)) = tokens.next() {
+                    if punct.as_char() != ',' {
+                        return Err("unexpected token after `compress`".to_string());
+                    }
+                }
+            }
+            _ => return Err("the only option of include_assets! is `compress`".to_string()),
+        },
+        _ => return Err("expected `,` after the path".to_string()),
+    }
+    if tokens.next().is_some() {
+        return Err("include_assets! takes at most two arguments".to_string());
+    }
+
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?;
+    let root = Path::new(&manifest_dir).join(dir);
+    let mut files = Vec::new();
+    collect_files(&root, &mut files)
+        .map_err(|e| format!("failed to read {}: {}", root.display(), e))?;
+    files.sort();
+
+    let mut assets = String::new();
+    for file in files {
+        let path = file
+            .strip_prefix(&root)
+            .unwrap()
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        let absolute = file.to_string_lossy().into_owned();
+        let data = if compress {
+            let contents = fs::read(&file)
+                .map_err(|e| format!("failed to read {}: {}", file.display(), e))?;
+            // The `include_bytes!` isn't used, but makes Cargo rebuild when the file changes.
+            format!(
+                "{{ const _: &[u8] = include_bytes!({:?}); {} }}",
+                absolute,
+                Literal::byte_string(&deflate(&contents)?)
+            )
+        } else {
+            format!("include_bytes!({:?})", absolute)
+        };
+        assets.push_str(&format!(
+            "::web_view::Asset {{ path: {:?}, data: {}, compressed: {} }},",
+            path, data, compress
+        ));
+    }
+    Ok(format!("::web_view::Assets::new(&[{}])", assets)
+        .parse()
+        .unwrap())
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "compress")]
+fn deflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    Ok(miniz_oxide::deflate::compress_to_vec(data, 9))
+}
+
+#[cfg(not(feature = "compress"))]
+fn deflate(_data: &[u8]) -> Result<Vec<u8>, String> {
+    Err("compressing assets requires the `compress-assets` feature of web-view".to_string())
+}
+
+/// Returns the contents of a string literal, or `None` if it isn't one.
+fn parse_str_literal(literal: &str) -> Option<String> {
+    if let Some(raw) = literal.strip_prefix('r') {
+        let hashes = raw.chars().take_while(|&c| c == '#').count();
+        let inner = &raw[hashes..raw.len() - hashes];
+        return Some(inner.strip_prefix('"')?.strip_suffix('"')?.to_string());
+    }
+    let inner = literal.strip_prefix('"')?.strip_suffix('"')?;
+    let mut result = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                'n' => result.push('\n'),
+                't' => result.push('\t'),
+                '0' => result.push('\0'),
+                c => result.push(c),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Some(result)
+}
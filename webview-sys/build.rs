@@ -9,8 +9,17 @@ fn main() {
     if target.contains("linux") || target.contains("bsd") {
         // linux or bsd need to link to webkit2gtk library only,
         // there is no C/C++ code to compile as in other platforms
+        let version = if cfg!(feature = "webkit2gtk-2-40") {
+            "2.40"
+        } else if cfg!(feature = "webkit2gtk-2-36") {
+            "2.36"
+        } else if cfg!(feature = "webkit2gtk-2-24") {
+            "2.24"
+        } else {
+            "2.8"
+        };
         pkg_config::Config::new()
-            .atleast_version("2.8")
+            .atleast_version(version)
             .probe("webkit2gtk-4.0")
             .unwrap();
 
@@ -1,18 +1,107 @@
 #![cfg(all(target_family = "unix", not(target_os = "macos")))]
 
-use gdk_sys::{gdk_threads_add_idle, GdkGeometry, GdkRGBA, GDK_HINT_MIN_SIZE};
-use gio_sys::GAsyncResult;
+use crate::{
+    CRect, CWindowConfig, CWindowFeatures, WEBVIEW_LOAD_FAILED, WEBVIEW_MODIFIER_ALT,
+    WEBVIEW_MODIFIER_CONTROL, WEBVIEW_MODIFIER_META, WEBVIEW_MODIFIER_SHIFT, WEBVIEW_POLICY_ALLOW,
+    WEBVIEW_POLICY_DENY, WEBVIEW_POLICY_OPEN_EXTERNALLY, WEBVIEW_POLICY_OPEN_NEW_WINDOW,
+    WEBVIEW_WINDOW_CLOSE_REQUESTED, WEBVIEW_WINDOW_FOCUSED, WEBVIEW_WINDOW_FULLSCREEN_ENTERED,
+    WEBVIEW_WINDOW_FULLSCREEN_LEFT, WEBVIEW_WINDOW_MAXIMIZED, WEBVIEW_WINDOW_MINIMIZED,
+    WEBVIEW_WINDOW_MOVED, WEBVIEW_WINDOW_RESIZED, WEBVIEW_WINDOW_RESTORED,
+    WEBVIEW_WINDOW_TYPE_DIALOG, WEBVIEW_WINDOW_TYPE_DOCK, WEBVIEW_WINDOW_TYPE_SPLASH,
+    WEBVIEW_WINDOW_TYPE_UTILITY, WEBVIEW_WINDOW_UNFOCUSED,
+};
+use gdk_pixbuf_sys::{
+    gdk_pixbuf_get_pixels, gdk_pixbuf_get_rowstride, gdk_pixbuf_loader_close,
+    gdk_pixbuf_loader_get_pixbuf, gdk_pixbuf_loader_new, gdk_pixbuf_loader_write, gdk_pixbuf_new,
+    GdkPixbuf, GDK_COLORSPACE_RGB,
+};
+use gdk_sys::{
+    gdk_screen_get_monitor_at_window, gdk_screen_get_monitor_workarea, gdk_screen_get_n_monitors,
+    gdk_screen_get_rgba_visual, gdk_screen_get_system_visual, gdk_threads_add_idle,
+    gdk_window_get_frame_extents, GdkEvent, GdkEventConfigure, GdkEventWindowState, GdkGeometry,
+    GdkRGBA, GdkRectangle, GDK_CONTROL_MASK, GDK_CURRENT_TIME, GDK_HINT_ASPECT, GDK_HINT_MAX_SIZE,
+    GDK_HINT_MIN_SIZE, GDK_META_MASK, GDK_MOD1_MASK, GDK_SHIFT_MASK, GDK_SUPER_MASK,
+    GDK_WINDOW_STATE_FULLSCREEN, GDK_WINDOW_STATE_ICONIFIED, GDK_WINDOW_STATE_MAXIMIZED,
+    GDK_WINDOW_TYPE_HINT_DIALOG, GDK_WINDOW_TYPE_HINT_DOCK, GDK_WINDOW_TYPE_HINT_NORMAL,
+    GDK_WINDOW_TYPE_HINT_SPLASHSCREEN, GDK_WINDOW_TYPE_HINT_UTILITY,
+};
+#[cfg(feature = "webkit2gtk-2-24")]
+use gio_sys::{g_cancellable_cancel, g_cancellable_is_cancelled, g_cancellable_new};
+use gio_sys::{
+    g_input_stream_get_type, g_io_error_quark, g_memory_input_stream_new_from_data, GAsyncResult,
+    GCancellable, GInputStream, GInputStreamClass, G_IO_ERROR_FAILED,
+};
 use glib_sys::*;
-use gobject_sys::{g_signal_connect_data, GObject};
+use gobject_sys::{
+    g_object_get_data, g_object_new, g_object_ref, g_object_set_data, g_object_unref,
+    g_signal_connect_data, g_type_register_static_simple, GObject,
+};
 use gtk_sys::*;
-use javascriptcore_sys::*;
-use libc::{c_char, c_double, c_int, c_void};
-use std::ffi::CStr;
+use javascriptcore_rs_sys::*;
+use libc::{c_char, c_double, c_int, c_uint, c_void};
+#[cfg(feature = "webkit2gtk-2-36")]
+use soup_sys::{
+    soup_message_headers_append, soup_message_headers_foreach, soup_message_headers_new,
+    SOUP_MESSAGE_HEADERS_RESPONSE,
+};
+use std::cell::RefCell;
+#[cfg(feature = "webkit2gtk-2-24")]
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::ffi::{CStr, CString, OsStr};
+use std::fs;
+#[cfg(feature = "webkit2gtk-2-24")]
+use std::hash::{Hash, Hasher};
 use std::mem;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
 use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
 use webkit2gtk_sys::*;
 
 type ExternalInvokeCallback = extern "C" fn(webview: *mut WebView, arg: *const c_char);
+type LoadCallback =
+    extern "C" fn(webview: *mut WebView, event: c_int, url: *const c_char, error: *const c_char);
+type NavigationCallback = extern "C" fn(
+    webview: *mut WebView,
+    url: *const c_char,
+    navigation_type: c_int,
+    target_frame: *const c_char,
+    modifiers: c_int,
+) -> c_int;
+type NewWindowCallback = extern "C" fn(
+    webview: *mut WebView,
+    url: *const c_char,
+    features: *const CWindowFeatures,
+    config: *mut CWindowConfig,
+) -> c_int;
+type WindowEventCallback =
+    extern "C" fn(webview: *mut WebView, event: c_int, a: c_int, b: c_int) -> c_int;
+type SchemeCallback = extern "C" fn(webview: *mut WebView, request: *mut c_void);
+type HeaderCallback = extern "C" fn(arg: *mut c_void, name: *const c_char, value: *const c_char);
+type BodyCallback = extern "C" fn(arg: *mut c_void, data: *const c_void, len: usize);
+type ReadFn = extern "C" fn(arg: *mut c_void, buf: *mut c_char, len: usize) -> i64;
+type CloseFn = extern "C" fn(arg: *mut c_void);
+type TimerFn = extern "C" fn(webview: *mut WebView, arg: *mut c_void) -> c_int;
+type EvalResultCallback =
+    extern "C" fn(arg: *mut c_void, json: *const c_char, exception: *const c_char);
+
+extern "C" {
+    // Part of the JavaScriptCore C API, but not exposed by javascriptcore-rs-sys.
+    fn JSValueCreateJSONString(
+        ctx: JSGlobalContextRef,
+        value: JSValueRef,
+        indent: c_uint,
+        exception: *mut JSValueRef,
+    ) -> JSStringRef;
+    // javascriptcore-rs-sys declares the buffer as a `char **`.
+    fn JSStringGetUTF8CString(string: JSStringRef, buffer: *mut c_char, size: usize) -> usize;
+    // New in WebKitGTK 2.40, which webkit2gtk-sys doesn't cover yet.
+    #[cfg(feature = "webkit2gtk-2-40")]
+    fn webkit_uri_scheme_request_get_http_body(
+        request: *mut WebKitURISchemeRequest,
+    ) -> *mut GInputStream;
+}
 
 #[repr(C)]
 struct WebView {
@@ -26,17 +115,58 @@ struct WebView {
     visible: c_int,
     min_width: c_int,
     min_height: c_int,
+    max_width: c_int,
+    max_height: c_int,
+    aspect_ratio: c_double,
     hide_instead_of_close: c_int,
     external_invoke_cb: ExternalInvokeCallback,
+    load_cb: Option<LoadCallback>,
+    navigation_cb: Option<NavigationCallback>,
+    new_window_cb: Option<NewWindowCallback>,
+    window_event_cb: Option<WindowEventCallback>,
+    // Configure events are also sent for changes that don't affect the size or position.
+    last_size: (c_int, c_int),
+    last_position: (c_int, c_int),
+    schemes: Vec<(CString, SchemeCallback)>,
     window: *mut GtkWidget,
     scroller: *mut GtkWidget,
     webview: *mut GtkWidget,
+    content_manager: *mut WebKitUserContentManager,
+    user_scripts: Vec<*mut WebKitUserScript>,
+    style_sheets: Vec<(c_int, *mut WebKitUserStyleSheet)>,
+    next_style_sheet_id: c_int,
+    // Windows opened with `WEBVIEW_POLICY_OPEN_NEW_WINDOW` share their parent's callbacks and
+    // userdata, and are destroyed along with it. Their window events aren't reported, as the
+    // callback couldn't tell them apart from the parent's.
+    is_child: c_int,
+    parent: *mut WebView,
+    children: Vec<*mut WebView>,
+    allow_next_navigation: c_int,
+    // Windows opened by the page are hidden until their features are known and the new window
+    // callback has decided on them. Their first navigation waits for that decision.
+    requested_uri: Option<CString>,
+    waiting_to_show: c_int,
+    deferred_decision: *mut WebKitPolicyDecision,
+    // Set by `webview_load_file`, navigations to other local files are denied. With WebKitGTK
+    // 2.24, the filter blocks all other loads of them, and the page is loaded once the filter is
+    // compiled.
+    allowed_dir: Option<CString>,
+    #[cfg(feature = "webkit2gtk-2-24")]
+    file_filter: *mut WebKitUserContentFilter,
+    #[cfg(feature = "webkit2gtk-2-24")]
+    file_filter_cancellable: *mut GCancellable,
     inspector_window: *mut GtkWidget,
     queue: *mut GAsyncQueue,
+    timers: Vec<c_uint>,
     ready: c_int,
     js_busy: c_int,
+    // Evaluations with a result which wait for the page to load.
+    pending_evals: Vec<(CString, EvalResultCallback, *mut c_void)>,
     should_exit: c_int,
     userdata: *mut c_void,
+    // Set by `webview_set_view_data`, freed once the view is closed.
+    view_data: Option<(*mut c_void, CloseFn)>,
+    closed: c_int,
 }
 
 #[no_mangle]
@@ -86,6 +216,140 @@ unsafe extern "C" fn webview_set_visible(webview: *mut WebView, visible: c_int)
     }
 }
 
+#[no_mangle]
+unsafe extern "C" fn webview_get_size(
+    webview: *mut WebView,
+    width: *mut c_int,
+    height: *mut c_int,
+) {
+    gtk_window_get_size(mem::transmute((*webview).window), width, height);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_size(webview: *mut WebView, width: c_int, height: c_int) {
+    if (*webview).resizable == 0 {
+        gtk_widget_set_size_request((*webview).window, width, height);
+    }
+    gtk_window_resize(mem::transmute((*webview).window), width, height);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_get_position(webview: *mut WebView, x: *mut c_int, y: *mut c_int) {
+    gtk_window_get_position(mem::transmute((*webview).window), x, y);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_position(webview: *mut WebView, x: c_int, y: c_int) {
+    // Otherwise a window that isn't shown yet would still be centered when it is.
+    gtk_window_set_position(mem::transmute((*webview).window), GTK_WIN_POS_NONE);
+    gtk_window_move(mem::transmute((*webview).window), x, y);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_center(webview: *mut WebView) {
+    let window = (*webview).window;
+    if gtk_widget_get_realized(window) == GFALSE {
+        gtk_window_set_position(mem::transmute(window), GTK_WIN_POS_CENTER);
+        return;
+    }
+    // `GTK_WIN_POS_CENTER` only applies when the window is shown.
+    let gdk_window = gtk_widget_get_window(window);
+    let screen = gtk_window_get_screen(mem::transmute(window));
+    let monitor = gdk_screen_get_monitor_at_window(screen, gdk_window);
+    let mut area: GdkRectangle = mem::zeroed();
+    gdk_screen_get_monitor_workarea(screen, monitor, &mut area);
+    let mut frame: GdkRectangle = mem::zeroed();
+    gdk_window_get_frame_extents(gdk_window, &mut frame);
+    gtk_window_move(
+        mem::transmute(window),
+        area.x + (area.width - frame.width) / 2,
+        area.y + (area.height - frame.height) / 2,
+    );
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_max_size(webview: *mut WebView, width: c_int, height: c_int) {
+    (*webview).max_width = width;
+    (*webview).max_height = height;
+    webview_update_geometry_hints(webview);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_aspect_ratio(webview: *mut WebView, ratio: c_double) {
+    (*webview).aspect_ratio = ratio;
+    webview_update_geometry_hints(webview);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_get_monitors(
+    webview: *mut WebView,
+    work_areas: *mut CRect,
+    max_count: c_int,
+) -> c_int {
+    let screen = gtk_window_get_screen(mem::transmute((*webview).window));
+    let count = gdk_screen_get_n_monitors(screen);
+    for monitor in 0..count.min(max_count) {
+        let mut area: GdkRectangle = mem::zeroed();
+        gdk_screen_get_monitor_workarea(screen, monitor, &mut area);
+        *work_areas.offset(monitor as isize) = CRect {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: area.height,
+        };
+    }
+    count
+}
+
+// GTK replaces all hints at once, so they are kept in the webview.
+unsafe fn webview_update_geometry_hints(w: *mut WebView) {
+    let mut hints = 0;
+    // Windows that aren't resizable have a fixed size request instead.
+    if (*w).resizable > 0 {
+        hints |= GDK_HINT_MIN_SIZE;
+    }
+    if (*w).max_width >= 0 || (*w).max_height >= 0 {
+        hints |= GDK_HINT_MAX_SIZE;
+    }
+    if (*w).aspect_ratio > 0.0 {
+        hints |= GDK_HINT_ASPECT;
+    }
+    let unlimited = |size: c_int| if size < 0 { c_int::MAX } else { size };
+    let geometry = GdkGeometry {
+        min_width: (*w).min_width,
+        min_height: (*w).min_height,
+        max_width: unlimited((*w).max_width),
+        max_height: unlimited((*w).max_height),
+        base_width: 0,
+        base_height: 0,
+        width_inc: 0,
+        height_inc: 0,
+        min_aspect: (*w).aspect_ratio,
+        max_aspect: (*w).aspect_ratio,
+        win_gravity: 0,
+    };
+    gtk_window_set_geometry_hints(
+        mem::transmute((*w).window),
+        ptr::null_mut(),
+        mem::transmute(&geometry),
+        hints,
+    );
+}
+
+// GTK is initialized by the first window, or by an `App` before it.
+static GTK_INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+#[no_mangle]
+unsafe extern "C" fn webview_init() -> c_int {
+    if !GTK_INITIALIZED.load(Ordering::Relaxed) {
+        if gtk_init_check(ptr::null_mut(), ptr::null_mut()) == GFALSE {
+            return -1;
+        }
+        GTK_INITIALIZED.store(true, Ordering::Relaxed);
+    }
+    0
+}
+
 #[no_mangle]
 unsafe extern "C" fn webview_new(
     title: *const c_char,
@@ -102,6 +366,48 @@ unsafe extern "C" fn webview_new(
     external_invoke_cb: ExternalInvokeCallback,
     userdata: *mut c_void,
 ) -> *mut WebView {
+    let w = webview_create(
+        title,
+        url,
+        width,
+        height,
+        resizable,
+        debug,
+        frameless,
+        visible,
+        min_width,
+        min_height,
+        hide_instead_of_close,
+        external_invoke_cb,
+        userdata,
+        ptr::null_mut(),
+    );
+    if !w.is_null() && !url.is_null() {
+        webkit_web_view_load_uri(mem::transmute((*w).webview), url);
+    }
+    w
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn webview_create(
+    title: *const c_char,
+    url: *const c_char,
+    width: c_int,
+    height: c_int,
+    resizable: c_int,
+    debug: c_int,
+    frameless: c_int,
+    visible: c_int,
+    min_width: c_int,
+    min_height: c_int,
+    hide_instead_of_close: c_int,
+    external_invoke_cb: ExternalInvokeCallback,
+    userdata: *mut c_void,
+    related_view: *mut WebKitWebView,
+) -> *mut WebView {
+    if webview_init() != 0 {
+        return ptr::null_mut();
+    }
     let w = Box::new(WebView {
         url,
         title,
@@ -113,24 +419,50 @@ unsafe extern "C" fn webview_new(
         visible,
         min_width,
         min_height,
+        max_width: -1,
+        max_height: -1,
+        aspect_ratio: 0.0,
         hide_instead_of_close,
         external_invoke_cb,
+        load_cb: None,
+        navigation_cb: None,
+        new_window_cb: None,
+        window_event_cb: None,
+        last_size: (width, height),
+        last_position: (-1, -1),
+        schemes: Vec::new(),
         window: ptr::null_mut(),
         scroller: ptr::null_mut(),
         webview: ptr::null_mut(),
+        content_manager: ptr::null_mut(),
+        user_scripts: Vec::new(),
+        style_sheets: Vec::new(),
+        next_style_sheet_id: 0,
+        is_child: 0,
+        parent: ptr::null_mut(),
+        children: Vec::new(),
+        allow_next_navigation: 0,
+        requested_uri: None,
+        waiting_to_show: 0,
+        deferred_decision: ptr::null_mut(),
+        allowed_dir: None,
+        #[cfg(feature = "webkit2gtk-2-24")]
+        file_filter: ptr::null_mut(),
+        #[cfg(feature = "webkit2gtk-2-24")]
+        file_filter_cancellable: ptr::null_mut(),
         inspector_window: ptr::null_mut(),
         queue: ptr::null_mut(),
+        timers: Vec::new(),
         ready: 0,
         js_busy: 0,
+        pending_evals: Vec::new(),
         should_exit: 0,
         userdata,
+        view_data: None,
+        closed: 0,
     });
 
     let w = Box::into_raw(w);
-
-    if gtk_init_check(ptr::null_mut(), ptr::null_mut()) == GFALSE {
-        return ptr::null_mut();
-    }
     (*w).queue = g_async_queue_new();
 
     let window = gtk_window_new(GTK_WINDOW_TOPLEVEL);
@@ -139,28 +471,7 @@ unsafe extern "C" fn webview_new(
 
     if resizable > 0 {
         gtk_window_set_default_size(mem::transmute(window), width, height);
-
-        let window_properties = GdkGeometry {
-            min_width,
-            min_height,
-
-            max_width: 0,
-            max_height: 0,
-            base_width: 0,
-            base_height: 0,
-            width_inc: 0,
-            height_inc: 0,
-            min_aspect: 0.0,
-            max_aspect: 0.0,
-            win_gravity: 0,
-        };
-
-        gtk_window_set_geometry_hints(
-            mem::transmute(window),
-            ptr::null_mut(),
-            mem::transmute(&window_properties),
-            GDK_HINT_MIN_SIZE,
-        );
+        webview_update_geometry_hints(w);
     } else {
         gtk_widget_set_size_request(mem::transmute(window), width, height);
     }
@@ -177,6 +488,7 @@ unsafe extern "C" fn webview_new(
     (*w).scroller = scroller;
 
     let m = webkit_user_content_manager_new();
+    (*w).content_manager = m;
     webkit_user_content_manager_register_script_message_handler(
         m,
         CStr::from_bytes_with_nul_unchecked(b"external\0").as_ptr(),
@@ -191,15 +503,35 @@ unsafe extern "C" fn webview_new(
         0,
     );
 
-    let webview = webkit_web_view_new_with_user_content_manager(m);
+    let external = webkit_user_script_new(
+        CStr::from_bytes_with_nul_unchecked(b"window.external={invoke:function(x){window.webkit.messageHandlers.external.postMessage(x);}}\0").as_ptr(),
+        WEBKIT_USER_CONTENT_INJECT_TOP_FRAME,
+        WEBKIT_USER_SCRIPT_INJECT_AT_DOCUMENT_START,
+        ptr::null(),
+        ptr::null(),
+    );
+    webkit_user_content_manager_add_script(m, external);
+    webkit_user_script_unref(external);
+
+    let webview = if related_view.is_null() {
+        webkit_web_view_new_with_user_content_manager(m)
+    } else {
+        // Views opened by a page must share its web process, and with it the web context.
+        g_object_new(
+            webkit_web_view_get_type(),
+            CStr::from_bytes_with_nul_unchecked(b"related-view\0").as_ptr(),
+            related_view,
+            CStr::from_bytes_with_nul_unchecked(b"user-content-manager\0").as_ptr(),
+            m,
+            ptr::null::<c_char>(),
+        ) as *mut GtkWidget
+    };
     (*w).webview = webview;
-    webkit_web_view_load_uri(
+    // Lets the scheme handlers, which are registered on the shared context, find the view.
+    g_object_set_data(
         mem::transmute(webview),
-        if url.is_null() {
-            b"\0".as_ptr() as *const _
-        } else {
-            url
-        },
+        CStr::from_bytes_with_nul_unchecked(b"webview\0").as_ptr(),
+        mem::transmute(w),
     );
     g_signal_connect_data(
         mem::transmute(webview),
@@ -209,6 +541,30 @@ unsafe extern "C" fn webview_new(
         None,
         0,
     );
+    g_signal_connect_data(
+        mem::transmute(webview),
+        CStr::from_bytes_with_nul_unchecked(b"load-failed\0").as_ptr(),
+        Some(mem::transmute(webview_load_failed_cb as *const ())),
+        mem::transmute(w),
+        None,
+        0,
+    );
+    g_signal_connect_data(
+        mem::transmute(webview),
+        CStr::from_bytes_with_nul_unchecked(b"decide-policy\0").as_ptr(),
+        Some(mem::transmute(webview_decide_policy_cb as *const ())),
+        mem::transmute(w),
+        None,
+        0,
+    );
+    g_signal_connect_data(
+        mem::transmute(webview),
+        CStr::from_bytes_with_nul_unchecked(b"create\0").as_ptr(),
+        Some(mem::transmute(webview_create_cb as *const ())),
+        mem::transmute(w),
+        None,
+        0,
+    );
     gtk_container_add(mem::transmute(scroller), webview);
 
     let settings = webkit_web_view_get_settings(mem::transmute(webview));
@@ -234,18 +590,52 @@ unsafe extern "C" fn webview_new(
         gtk_widget_show_all(window);
     }
 
-    webkit_web_view_run_javascript(
-        mem::transmute(webview),
-        CStr::from_bytes_with_nul_unchecked(b"window.external={invoke:function(x){window.webkit.messageHandlers.external.postMessage(x);}}\0").as_ptr(),
-        ptr::null_mut(),
+    g_signal_connect_data(
+        mem::transmute(window),
+        CStr::from_bytes_with_nul_unchecked(b"destroy\0").as_ptr(),
+        Some(mem::transmute(webview_destroy_cb as *const ())),
+        mem::transmute(w),
         None,
-        ptr::null_mut(),
+        0,
     );
 
+    // Connected before hiding on delete, so closing can be vetoed before the window is hidden.
     g_signal_connect_data(
         mem::transmute(window),
-        CStr::from_bytes_with_nul_unchecked(b"destroy\0").as_ptr(),
-        Some(mem::transmute(webview_destroy_cb as *const ())),
+        CStr::from_bytes_with_nul_unchecked(b"delete-event\0").as_ptr(),
+        Some(mem::transmute(webview_delete_event_cb as *const ())),
+        mem::transmute(w),
+        None,
+        0,
+    );
+    g_signal_connect_data(
+        mem::transmute(window),
+        CStr::from_bytes_with_nul_unchecked(b"configure-event\0").as_ptr(),
+        Some(mem::transmute(webview_configure_event_cb as *const ())),
+        mem::transmute(w),
+        None,
+        0,
+    );
+    g_signal_connect_data(
+        mem::transmute(window),
+        CStr::from_bytes_with_nul_unchecked(b"focus-in-event\0").as_ptr(),
+        Some(mem::transmute(webview_focus_in_event_cb as *const ())),
+        mem::transmute(w),
+        None,
+        0,
+    );
+    g_signal_connect_data(
+        mem::transmute(window),
+        CStr::from_bytes_with_nul_unchecked(b"focus-out-event\0").as_ptr(),
+        Some(mem::transmute(webview_focus_out_event_cb as *const ())),
+        mem::transmute(w),
+        None,
+        0,
+    );
+    g_signal_connect_data(
+        mem::transmute(window),
+        CStr::from_bytes_with_nul_unchecked(b"window-state-event\0").as_ptr(),
+        Some(mem::transmute(webview_window_state_event_cb as *const ())),
         mem::transmute(w),
         None,
         0,
@@ -289,9 +679,78 @@ unsafe extern "C" fn webview_get_user_data(webview: *mut WebView) -> *mut c_void
     (*webview).userdata
 }
 
+#[no_mangle]
+unsafe extern "C" fn webview_set_view_data(
+    webview: *mut WebView,
+    data: *mut c_void,
+    free_fn: CloseFn,
+) -> c_int {
+    if (*webview).closed != 0 {
+        return -1;
+    }
+    webview_close_view_data(webview);
+    (*webview).view_data = Some((data, free_fn));
+    0
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_get_view_data(webview: *mut WebView) -> *mut c_void {
+    (*webview)
+        .view_data
+        .map_or(ptr::null_mut(), |(data, _)| data)
+}
+
+unsafe fn webview_close_view_data(webview: *mut WebView) {
+    if let Some((data, free_fn)) = (*webview).view_data.take() {
+        free_fn(data);
+    }
+}
+
 #[no_mangle]
 unsafe extern "C" fn webview_free(webview: *mut WebView) {
-    let _ = Box::from_raw(webview);
+    for &child in &(*webview).children {
+        // Keep the child from unregistering itself while we iterate.
+        (*child).parent = ptr::null_mut();
+        gtk_widget_destroy((*child).window);
+    }
+    (*webview).closed = 1;
+    webview_close_view_data(webview);
+    // Their callbacks must not outlive the webview.
+    for id in mem::take(&mut (*webview).timers) {
+        g_source_remove(id);
+    }
+    let webview = Box::from_raw(webview);
+    for &(_, cb, arg) in &webview.pending_evals {
+        cb(
+            arg,
+            ptr::null(),
+            CStr::from_bytes_with_nul_unchecked(b"The webview was closed\0").as_ptr(),
+        );
+    }
+    if !webview.deferred_decision.is_null() {
+        g_object_unref(mem::transmute(webview.deferred_decision));
+    }
+    for &script in &webview.user_scripts {
+        webkit_user_script_unref(script);
+    }
+    for &(_, style_sheet) in &webview.style_sheets {
+        webkit_user_style_sheet_unref(style_sheet);
+    }
+    #[cfg(feature = "webkit2gtk-2-24")]
+    {
+        if !webview.file_filter_cancellable.is_null() {
+            g_cancellable_cancel(webview.file_filter_cancellable);
+            g_object_unref(mem::transmute(webview.file_filter_cancellable));
+        }
+        if !webview.file_filter.is_null() {
+            webkit_user_content_filter_unref(webview.file_filter);
+        }
+    }
+}
+
+unsafe extern "C" fn webview_free_idle(arg: gpointer) -> gboolean {
+    webview_free(arg as *mut WebView);
+    GFALSE
 }
 
 #[no_mangle]
@@ -304,81 +763,1442 @@ unsafe extern "C" fn webview_loop(webview: *mut WebView, blocking: c_int) -> c_i
     (*webview).should_exit
 }
 
-#[no_mangle]
-unsafe extern "C" fn webview_set_color(webview: *mut WebView, r: u8, g: u8, b: u8, a: u8) {
-    let color = GdkRGBA {
-        red: r as c_double / 255.0,
-        green: g as c_double / 255.0,
-        blue: b as c_double / 255.0,
-        alpha: a as c_double / 255.0,
-    };
-    webkit_web_view_set_background_color(mem::transmute((*webview).webview), &color);
+unsafe extern "C" fn webview_loop_timeout_cb(arg: gpointer) -> gboolean {
+    *(arg as *mut gboolean) = GTRUE;
+    GFALSE
 }
 
 #[no_mangle]
-unsafe extern "C" fn webview_set_zoom_level(webview: *mut WebView, percentage: c_double) {
-    webkit_web_view_set_zoom_level(mem::transmute((*webview).webview), percentage);
+unsafe extern "C" fn webview_loop_timeout(webview: *mut WebView, timeout_ms: c_int) -> c_int {
+    // The timeout wakes up the blocking iteration if no other event arrives in time.
+    let mut timed_out = GFALSE;
+    let source = g_timeout_add(
+        timeout_ms.max(0) as c_uint,
+        Some(webview_loop_timeout_cb),
+        &mut timed_out as *mut gboolean as gpointer,
+    );
+    let should_exit = webview_loop(webview, 1);
+    if timed_out == GFALSE {
+        g_source_remove(source);
+    }
+    should_exit
 }
 
 #[no_mangle]
-unsafe extern "C" fn webview_set_html(webview: *mut WebView, html: *const c_char) {
-    webkit_web_view_load_html(
-        mem::transmute((*webview).webview),
-        html,
-        CStr::from_bytes_with_nul_unchecked(b"").as_ptr(),
-    );
+unsafe extern "C" fn webview_set_always_on_top(webview: *mut WebView, always_on_top: c_int) {
+    gtk_window_set_keep_above(mem::transmute((*webview).window), always_on_top);
 }
 
-unsafe extern "C" fn webview_load_changed_cb(
-    _webview: *mut WebKitWebView,
-    event: WebKitLoadEvent,
-    arg: gpointer,
-) {
-    let w: *mut WebView = mem::transmute(arg);
-    if event == WEBKIT_LOAD_FINISHED {
-        (*w).ready = 1;
-    }
+#[no_mangle]
+unsafe extern "C" fn webview_set_skip_taskbar(webview: *mut WebView, skip: c_int) {
+    gtk_window_set_skip_taskbar_hint(mem::transmute((*webview).window), skip);
 }
 
-unsafe extern "C" fn webview_eval_finished(
-    _object: *mut GObject,
-    _result: *mut GAsyncResult,
-    userdata: gpointer,
-) {
-    let webview: *mut WebView = mem::transmute(userdata);
-    (*webview).js_busy = 0;
+#[no_mangle]
+unsafe extern "C" fn webview_set_skip_pager(webview: *mut WebView, skip: c_int) {
+    gtk_window_set_skip_pager_hint(mem::transmute((*webview).window), skip);
 }
 
 #[no_mangle]
-unsafe extern "C" fn webview_eval(webview: *mut WebView, js: *const c_char) -> c_int {
-    while (*webview).ready == 0 {
-        g_main_context_iteration(ptr::null_mut(), GTRUE);
+unsafe extern "C" fn webview_set_sticky(webview: *mut WebView, sticky: c_int) {
+    if sticky != 0 {
+        gtk_window_stick(mem::transmute((*webview).window));
+    } else {
+        gtk_window_unstick(mem::transmute((*webview).window));
     }
+}
 
-    (*webview).js_busy = 1;
-
-    webkit_web_view_run_javascript(
-        mem::transmute((*webview).webview),
-        js,
-        ptr::null_mut(),
-        Some(webview_eval_finished),
-        mem::transmute(webview),
-    );
-
-    while (*webview).js_busy == 1 {
-        g_main_context_iteration(ptr::null_mut(), GTRUE);
+#[no_mangle]
+unsafe extern "C" fn webview_set_window_type(webview: *mut WebView, window_type: c_int) {
+    let hint = match window_type {
+        WEBVIEW_WINDOW_TYPE_UTILITY => GDK_WINDOW_TYPE_HINT_UTILITY,
+        WEBVIEW_WINDOW_TYPE_DIALOG => GDK_WINDOW_TYPE_HINT_DIALOG,
+        WEBVIEW_WINDOW_TYPE_SPLASH => GDK_WINDOW_TYPE_HINT_SPLASHSCREEN,
+        WEBVIEW_WINDOW_TYPE_DOCK => GDK_WINDOW_TYPE_HINT_DOCK,
+        _ => GDK_WINDOW_TYPE_HINT_NORMAL,
+    };
+    let window = (*webview).window;
+    // The hint only applies once the window is mapped again, which forgets its position.
+    if gtk_widget_get_visible(window) != GFALSE {
+        let (mut x, mut y) = (0, 0);
+        gtk_window_get_position(mem::transmute(window), &mut x, &mut y);
+        gtk_widget_hide(window);
+        gtk_window_set_type_hint(mem::transmute(window), hint);
+        gtk_window_move(mem::transmute(window), x, y);
+        gtk_widget_show_all(window);
+    } else {
+        gtk_window_set_type_hint(mem::transmute(window), hint);
     }
-
-    0
 }
 
-type DispatchFn = extern "C" fn(webview: *mut WebView, arg: *mut c_void);
-
-#[repr(C)]
-struct DispatchArg {
-    func: DispatchFn,
+#[no_mangle]
+unsafe extern "C" fn webview_set_icon(
     webview: *mut WebView,
-    arg: *mut c_void,
+    data: *const u8,
+    len: usize,
+    width: c_int,
+    height: c_int,
+) -> c_int {
+    let pixbuf = if width > 0 && height > 0 {
+        icon_from_rgba(data, len, width, height)
+    } else {
+        icon_from_file_contents(data, len)
+    };
+    if pixbuf.is_null() {
+        return -1;
+    }
+    gtk_window_set_icon(mem::transmute((*webview).window), pixbuf);
+    g_object_unref(mem::transmute(pixbuf));
+    0
+}
+
+unsafe fn icon_from_rgba(
+    data: *const u8,
+    len: usize,
+    width: c_int,
+    height: c_int,
+) -> *mut GdkPixbuf {
+    let row_len = width as usize * 4;
+    if len < row_len * height as usize {
+        return ptr::null_mut();
+    }
+    let pixbuf = gdk_pixbuf_new(GDK_COLORSPACE_RGB, GTRUE, 8, width, height);
+    if pixbuf.is_null() {
+        return ptr::null_mut();
+    }
+    // Rows of the pixbuf may be padded.
+    let pixels = gdk_pixbuf_get_pixels(pixbuf);
+    let stride = gdk_pixbuf_get_rowstride(pixbuf) as usize;
+    for row in 0..height as usize {
+        ptr::copy_nonoverlapping(data.add(row * row_len), pixels.add(row * stride), row_len);
+    }
+    pixbuf
+}
+
+unsafe fn icon_from_file_contents(data: *const u8, len: usize) -> *mut GdkPixbuf {
+    let loader = gdk_pixbuf_loader_new();
+    // The loader must be closed even if writing failed.
+    let written = gdk_pixbuf_loader_write(loader, data, len, ptr::null_mut()) != GFALSE;
+    let closed = gdk_pixbuf_loader_close(loader, ptr::null_mut()) != GFALSE;
+    let mut pixbuf = ptr::null_mut();
+    if written && closed {
+        pixbuf = gdk_pixbuf_loader_get_pixbuf(loader);
+        if !pixbuf.is_null() {
+            g_object_ref(mem::transmute(pixbuf));
+        }
+    }
+    g_object_unref(mem::transmute(loader));
+    pixbuf
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_transparent(webview: *mut WebView, transparent: c_int) {
+    let window = (*webview).window;
+    let screen = gtk_widget_get_screen(window);
+    // Without a compositor there is no RGBA visual, and the window stays opaque.
+    let mut visual = ptr::null_mut();
+    if transparent != 0 {
+        visual = gdk_screen_get_rgba_visual(screen);
+    }
+    if visual.is_null() {
+        visual = gdk_screen_get_system_visual(screen);
+    }
+    gtk_widget_set_visual(window, visual);
+    // Keeps GTK from painting the background of the window.
+    gtk_widget_set_app_paintable(window, transparent);
+    let color = GdkRGBA {
+        red: 1.0,
+        green: 1.0,
+        blue: 1.0,
+        alpha: if transparent != 0 { 0.0 } else { 1.0 },
+    };
+    webkit_web_view_set_background_color(mem::transmute((*webview).webview), &color);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_color(webview: *mut WebView, r: u8, g: u8, b: u8, a: u8) {
+    let color = GdkRGBA {
+        red: r as c_double / 255.0,
+        green: g as c_double / 255.0,
+        blue: b as c_double / 255.0,
+        alpha: a as c_double / 255.0,
+    };
+    webkit_web_view_set_background_color(mem::transmute((*webview).webview), &color);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_zoom_level(webview: *mut WebView, percentage: c_double) {
+    webkit_web_view_set_zoom_level(mem::transmute((*webview).webview), percentage);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_get_zoom_level(webview: *mut WebView) -> c_double {
+    webkit_web_view_get_zoom_level(mem::transmute((*webview).webview))
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_html(
+    webview: *mut WebView,
+    html: *const c_char,
+    base_url: *const c_char,
+) {
+    webkit_web_view_load_html(mem::transmute((*webview).webview), html, base_url);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_navigate(webview: *mut WebView, url: *const c_char) {
+    webkit_web_view_load_uri(mem::transmute((*webview).webview), url);
+}
+
+#[cfg(not(feature = "webkit2gtk-2-24"))]
+#[no_mangle]
+unsafe extern "C" fn webview_load_file(
+    webview: *mut WebView,
+    url: *const c_char,
+    allowed_dir: *const c_char,
+) {
+    // Without a content filter only navigations to other local files can be denied. The page
+    // can still load local images, scripts and the like, but not `fetch` local files, which
+    // WebKit denies by default.
+    (*webview).allowed_dir = Some(webview_canonical_dir(allowed_dir));
+    webkit_web_view_load_uri(mem::transmute((*webview).webview), url);
+}
+
+#[cfg(feature = "webkit2gtk-2-24")]
+#[no_mangle]
+unsafe extern "C" fn webview_load_file(
+    webview: *mut WebView,
+    url: *const c_char,
+    allowed_dir: *const c_char,
+) {
+    let allowed_dir = webview_canonical_dir(allowed_dir);
+    let settings = webkit_web_view_get_settings(mem::transmute((*webview).webview));
+    // Lets the page fetch other files with relative URLs, WebKit denies any file access from
+    // file: pages by default.
+    webkit_settings_set_allow_file_access_from_file_urls(settings, GTRUE);
+
+    // WebKit only asks about navigations, so images, scripts, fetch and the like are kept in
+    // the allowed directory by a content filter. It compares URLs, so unlike navigations, loads
+    // through symlinks in the directory aren't resolved. Compiled filters are kept in the user's
+    // cache directory, one per allowed directory, and reused by later loads.
+    let source = webview_file_filter_source(allowed_dir.to_bytes());
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    let identifier = CString::new(format!("webview-file-access-{:016x}", hasher.finish())).unwrap();
+    let mut storage_path = CStr::from_ptr(g_get_user_cache_dir()).to_bytes().to_vec();
+    storage_path.extend_from_slice(b"/webview/content-filters");
+    let storage_path = CString::new(storage_path).unwrap();
+    let store = webkit_user_content_filter_store_new(storage_path.as_ptr());
+    let bytes = g_bytes_new(source.as_ptr() as gconstpointer, source.len());
+
+    if !(*webview).file_filter_cancellable.is_null() {
+        g_cancellable_cancel((*webview).file_filter_cancellable);
+        g_object_unref(mem::transmute((*webview).file_filter_cancellable));
+    }
+    let cancellable = g_cancellable_new();
+    (*webview).file_filter_cancellable = cancellable;
+    (*webview).allowed_dir = Some(allowed_dir);
+    let load = Box::new(FileLoad {
+        webview,
+        url: CStr::from_ptr(url).to_owned(),
+        cancellable: mem::transmute(g_object_ref(mem::transmute(cancellable))),
+    });
+    webkit_user_content_filter_store_save(
+        store,
+        identifier.as_ptr(),
+        bytes,
+        cancellable,
+        Some(webview_file_filter_saved),
+        Box::into_raw(load) as gpointer,
+    );
+    g_bytes_unref(bytes);
+    g_object_unref(mem::transmute(store));
+}
+
+#[cfg(feature = "webkit2gtk-2-24")]
+struct FileLoad {
+    webview: *mut WebView,
+    url: CString,
+    cancellable: *mut GCancellable,
+}
+
+#[cfg(feature = "webkit2gtk-2-24")]
+unsafe extern "C" fn webview_file_filter_saved(
+    store: *mut GObject,
+    result: *mut GAsyncResult,
+    arg: gpointer,
+) {
+    let load = Box::from_raw(arg as *mut FileLoad);
+    let mut error = ptr::null_mut();
+    let filter = webkit_user_content_filter_store_save_finish(
+        store as *mut WebKitUserContentFilterStore,
+        result,
+        &mut error,
+    );
+    // The webview was freed, or loads another file.
+    let cancelled = g_cancellable_is_cancelled(load.cancellable) != GFALSE;
+    g_object_unref(mem::transmute(load.cancellable));
+    if cancelled {
+        if !filter.is_null() {
+            webkit_user_content_filter_unref(filter);
+        }
+        if !error.is_null() {
+            g_error_free(error);
+        }
+        return;
+    }
+
+    let webview = load.webview;
+    g_object_unref(mem::transmute((*webview).file_filter_cancellable));
+    (*webview).file_filter_cancellable = ptr::null_mut();
+    if filter.is_null() {
+        // Without the filter the page could read any file, so it isn't loaded.
+        if let Some(load_cb) = (*webview).load_cb {
+            load_cb(
+                webview,
+                WEBVIEW_LOAD_FAILED,
+                load.url.as_ptr(),
+                (*error).message,
+            );
+        }
+        g_error_free(error);
+        return;
+    }
+    webview_set_file_filter(webview, filter);
+    webkit_user_content_filter_unref(filter);
+    webkit_web_view_load_uri(mem::transmute((*webview).webview), load.url.as_ptr());
+}
+
+#[cfg(feature = "webkit2gtk-2-24")]
+unsafe fn webview_set_file_filter(webview: *mut WebView, filter: *mut WebKitUserContentFilter) {
+    let old = mem::replace(
+        &mut (*webview).file_filter,
+        webkit_user_content_filter_ref(filter),
+    );
+    if !old.is_null() {
+        webkit_user_content_manager_remove_filter((*webview).content_manager, old);
+        webkit_user_content_filter_unref(old);
+    }
+    webkit_user_content_manager_add_filter((*webview).content_manager, filter);
+}
+
+// A content blocker rule list that blocks all file: URLs, except those in `dir`. The URLs of
+// requests are compared after parsing, which percent-encodes the same characters as here.
+#[cfg(feature = "webkit2gtk-2-24")]
+fn webview_file_filter_source(dir: &[u8]) -> String {
+    let mut prefix = String::from("^file://");
+    for &byte in dir {
+        if byte <= b' ' || byte >= 0x7f || b"\"#%<>?\\`{}".contains(&byte) {
+            prefix.push_str(&format!("%{:02X}", byte));
+        } else {
+            if b".*+?()[]|^$".contains(&byte) {
+                // Escaped for the regular expression, and that backslash for JSON.
+                prefix.push_str("\\\\");
+            }
+            prefix.push(byte as char);
+        }
+    }
+    if !prefix.ends_with('/') {
+        prefix.push('/');
+    }
+    format!(
+        "[{{\"trigger\":{{\"url-filter\":\"^file:\",\"url-filter-is-case-sensitive\":true}},\
+         \"action\":{{\"type\":\"block\"}}}},\
+         {{\"trigger\":{{\"url-filter\":\"{}\",\"url-filter-is-case-sensitive\":true}},\
+         \"action\":{{\"type\":\"ignore-previous-rules\"}}}}]",
+        prefix
+    )
+}
+
+// Resolves symlinks, so a link in the allowed directory can't lead out of it.
+fn webview_canonical_path(path: &CStr) -> Option<CString> {
+    let path = fs::canonicalize(OsStr::from_bytes(path.to_bytes())).ok()?;
+    CString::new(path.into_os_string().into_vec()).ok()
+}
+
+unsafe fn webview_canonical_dir(dir: *const c_char) -> CString {
+    let dir = CStr::from_ptr(dir);
+    webview_canonical_path(dir).unwrap_or_else(|| dir.to_owned())
+}
+
+unsafe fn webview_is_allowed_file(webview: *mut WebView, uri: *const c_char) -> bool {
+    let allowed_dir = match (*webview).allowed_dir {
+        Some(ref allowed_dir) => allowed_dir.to_bytes(),
+        None => return true,
+    };
+    let path = g_filename_from_uri(uri, ptr::null_mut(), ptr::null_mut());
+    if path.is_null() {
+        // Not a file: URI.
+        return true;
+    }
+    let allowed = match webview_canonical_path(CStr::from_ptr(path)) {
+        Some(path) => {
+            let path = path.to_bytes();
+            path.starts_with(allowed_dir)
+                && (path.len() == allowed_dir.len()
+                    || allowed_dir.ends_with(b"/")
+                    || path[allowed_dir.len()] == b'/')
+        }
+        // A file which doesn't exist couldn't be loaded anyway.
+        None => false,
+    };
+    g_free(path as gpointer);
+    allowed
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_reload(webview: *mut WebView, bypass_cache: c_int) {
+    if bypass_cache != 0 {
+        webkit_web_view_reload_bypass_cache(mem::transmute((*webview).webview));
+    } else {
+        webkit_web_view_reload(mem::transmute((*webview).webview));
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_go_back(webview: *mut WebView) {
+    webkit_web_view_go_back(mem::transmute((*webview).webview));
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_go_forward(webview: *mut WebView) {
+    webkit_web_view_go_forward(mem::transmute((*webview).webview));
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_can_go_back(webview: *mut WebView) -> c_int {
+    webkit_web_view_can_go_back(mem::transmute((*webview).webview))
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_can_go_forward(webview: *mut WebView) -> c_int {
+    webkit_web_view_can_go_forward(mem::transmute((*webview).webview))
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_stop_loading(webview: *mut WebView) {
+    webkit_web_view_stop_loading(mem::transmute((*webview).webview));
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_get_url(webview: *mut WebView) -> *const c_char {
+    webkit_web_view_get_uri(mem::transmute((*webview).webview))
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_get_page_title(webview: *mut WebView) -> *const c_char {
+    webkit_web_view_get_title(mem::transmute((*webview).webview))
+}
+
+unsafe extern "C" fn webview_load_changed_cb(
+    webview: *mut WebKitWebView,
+    event: WebKitLoadEvent,
+    arg: gpointer,
+) {
+    let w: *mut WebView = mem::transmute(arg);
+    if event == WEBKIT_LOAD_FINISHED {
+        (*w).ready = 1;
+        for (js, cb, arg) in mem::take(&mut (*w).pending_evals) {
+            webview_run_javascript_with_result(w, js.as_ptr(), cb, arg);
+        }
+    }
+    if let Some(load_cb) = (*w).load_cb {
+        load_cb(
+            w,
+            event as c_int,
+            webkit_web_view_get_uri(webview),
+            ptr::null(),
+        );
+    }
+}
+
+unsafe extern "C" fn webview_load_failed_cb(
+    _webview: *mut WebKitWebView,
+    _event: WebKitLoadEvent,
+    failing_uri: *const c_char,
+    error: *mut GError,
+    arg: gpointer,
+) -> gboolean {
+    let w: *mut WebView = mem::transmute(arg);
+    if let Some(load_cb) = (*w).load_cb {
+        load_cb(w, WEBVIEW_LOAD_FAILED, failing_uri, (*error).message);
+    }
+    // Let WebKit show its error page.
+    GFALSE
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_get_load_progress(webview: *mut WebView) -> c_double {
+    webkit_web_view_get_estimated_load_progress(mem::transmute((*webview).webview))
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_load_cb(webview: *mut WebView, load_cb: Option<LoadCallback>) {
+    (*webview).load_cb = load_cb;
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_navigation_cb(
+    webview: *mut WebView,
+    navigation_cb: Option<NavigationCallback>,
+) {
+    (*webview).navigation_cb = navigation_cb;
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_window_event_cb(
+    webview: *mut WebView,
+    window_event_cb: Option<WindowEventCallback>,
+) {
+    (*webview).window_event_cb = window_event_cb;
+}
+
+unsafe fn webview_window_event(webview: *mut WebView, event: c_int, a: c_int, b: c_int) -> c_int {
+    match (*webview).window_event_cb {
+        Some(window_event_cb) => window_event_cb(webview, event, a, b),
+        None => 0,
+    }
+}
+
+unsafe extern "C" fn webview_delete_event_cb(
+    _widget: *mut GtkWidget,
+    _event: *mut GdkEvent,
+    arg: gpointer,
+) -> gboolean {
+    let w: *mut WebView = mem::transmute(arg);
+    // `webview_exit` closes the window too, which must not be vetoed.
+    if (*w).should_exit != 0 {
+        return GFALSE;
+    }
+    if webview_window_event(w, WEBVIEW_WINDOW_CLOSE_REQUESTED, 0, 0) != 0 {
+        GTRUE
+    } else {
+        GFALSE
+    }
+}
+
+unsafe extern "C" fn webview_configure_event_cb(
+    widget: *mut GtkWidget,
+    _event: *mut GdkEventConfigure,
+    arg: gpointer,
+) -> gboolean {
+    let w: *mut WebView = mem::transmute(arg);
+    // Unlike the event, GTK reports the position of the window frame, like it is set.
+    let (mut x, mut y, mut width, mut height) = (0, 0, 0, 0);
+    gtk_window_get_position(mem::transmute(widget), &mut x, &mut y);
+    gtk_window_get_size(mem::transmute(widget), &mut width, &mut height);
+    if (width, height) != (*w).last_size {
+        (*w).last_size = (width, height);
+        webview_window_event(w, WEBVIEW_WINDOW_RESIZED, width, height);
+    }
+    if (x, y) != (*w).last_position {
+        (*w).last_position = (x, y);
+        webview_window_event(w, WEBVIEW_WINDOW_MOVED, x, y);
+    }
+    GFALSE
+}
+
+unsafe extern "C" fn webview_focus_in_event_cb(
+    _widget: *mut GtkWidget,
+    _event: *mut GdkEvent,
+    arg: gpointer,
+) -> gboolean {
+    webview_window_event(mem::transmute(arg), WEBVIEW_WINDOW_FOCUSED, 0, 0);
+    GFALSE
+}
+
+unsafe extern "C" fn webview_focus_out_event_cb(
+    _widget: *mut GtkWidget,
+    _event: *mut GdkEvent,
+    arg: gpointer,
+) -> gboolean {
+    webview_window_event(mem::transmute(arg), WEBVIEW_WINDOW_UNFOCUSED, 0, 0);
+    GFALSE
+}
+
+unsafe extern "C" fn webview_window_state_event_cb(
+    _widget: *mut GtkWidget,
+    event: *mut GdkEventWindowState,
+    arg: gpointer,
+) -> gboolean {
+    let w: *mut WebView = mem::transmute(arg);
+    let changed = (*event).changed_mask;
+    let state = (*event).new_window_state;
+    if changed & GDK_WINDOW_STATE_FULLSCREEN != 0 {
+        if state & GDK_WINDOW_STATE_FULLSCREEN != 0 {
+            webview_window_event(w, WEBVIEW_WINDOW_FULLSCREEN_ENTERED, 0, 0);
+        } else {
+            webview_window_event(w, WEBVIEW_WINDOW_FULLSCREEN_LEFT, 0, 0);
+        }
+    }
+    if changed & (GDK_WINDOW_STATE_ICONIFIED | GDK_WINDOW_STATE_MAXIMIZED) != 0 {
+        if state & GDK_WINDOW_STATE_ICONIFIED != 0 {
+            webview_window_event(w, WEBVIEW_WINDOW_MINIMIZED, 0, 0);
+        } else if state & GDK_WINDOW_STATE_MAXIMIZED != 0 {
+            webview_window_event(w, WEBVIEW_WINDOW_MAXIMIZED, 0, 0);
+        } else {
+            webview_window_event(w, WEBVIEW_WINDOW_RESTORED, 0, 0);
+        }
+    }
+    GFALSE
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_set_new_window_cb(
+    webview: *mut WebView,
+    new_window_cb: Option<NewWindowCallback>,
+) {
+    (*webview).new_window_cb = new_window_cb;
+}
+
+fn modifiers_from_gdk(state: c_uint) -> c_int {
+    let mut modifiers = 0;
+    if state & GDK_SHIFT_MASK != 0 {
+        modifiers |= WEBVIEW_MODIFIER_SHIFT;
+    }
+    if state & GDK_CONTROL_MASK != 0 {
+        modifiers |= WEBVIEW_MODIFIER_CONTROL;
+    }
+    if state & GDK_MOD1_MASK != 0 {
+        modifiers |= WEBVIEW_MODIFIER_ALT;
+    }
+    if state & (GDK_META_MASK | GDK_SUPER_MASK) != 0 {
+        modifiers |= WEBVIEW_MODIFIER_META;
+    }
+    modifiers
+}
+
+unsafe extern "C" fn webview_decide_policy_cb(
+    _webview: *mut WebKitWebView,
+    decision: *mut WebKitPolicyDecision,
+    decision_type: WebKitPolicyDecisionType,
+    arg: gpointer,
+) -> gboolean {
+    let w: *mut WebView = mem::transmute(arg);
+    if decision_type != WEBKIT_POLICY_DECISION_TYPE_NAVIGATION_ACTION
+        && decision_type != WEBKIT_POLICY_DECISION_TYPE_NEW_WINDOW_ACTION
+    {
+        return GFALSE;
+    }
+    let uri = webkit_uri_request_get_uri(webkit_navigation_action_get_request(
+        webkit_navigation_policy_decision_get_navigation_action(
+            decision as *mut WebKitNavigationPolicyDecision,
+        ),
+    ));
+    if !webview_is_allowed_file(w, uri) {
+        webkit_policy_decision_ignore(decision);
+        return GTRUE;
+    }
+    if (*w).waiting_to_show != 0 && (*w).deferred_decision.is_null() {
+        g_object_ref(mem::transmute(decision));
+        (*w).deferred_decision = decision;
+        return GTRUE;
+    }
+    // The first navigation of a window we opened has already been decided on.
+    if (*w).allow_next_navigation != 0 {
+        (*w).allow_next_navigation = 0;
+        return GFALSE;
+    }
+    let navigation_cb = match (*w).navigation_cb {
+        Some(navigation_cb) => navigation_cb,
+        None => return GFALSE,
+    };
+
+    let navigation = decision as *mut WebKitNavigationPolicyDecision;
+    let action = webkit_navigation_policy_decision_get_navigation_action(navigation);
+    let policy = navigation_cb(
+        w,
+        uri,
+        // Matches the order of `webview_navigation_type`.
+        webkit_navigation_action_get_navigation_type(action) as c_int,
+        webkit_navigation_policy_decision_get_frame_name(navigation),
+        modifiers_from_gdk(webkit_navigation_action_get_modifiers(action)),
+    );
+
+    match policy {
+        WEBVIEW_POLICY_ALLOW => {
+            webkit_policy_decision_use(decision);
+        }
+        WEBVIEW_POLICY_OPEN_EXTERNALLY => {
+            gtk_show_uri(
+                ptr::null_mut(),
+                uri,
+                GDK_CURRENT_TIME as u32,
+                ptr::null_mut(),
+            );
+            webkit_policy_decision_ignore(decision);
+        }
+        WEBVIEW_POLICY_OPEN_NEW_WINDOW => {
+            webview_open_child(w, uri);
+            webkit_policy_decision_ignore(decision);
+        }
+        _ => {
+            webkit_policy_decision_ignore(decision);
+        }
+    }
+    GTRUE
+}
+
+unsafe fn webview_open_child(parent: *mut WebView, uri: *const c_char) {
+    let child = webview_create_child(parent, uri, 1, ptr::null_mut());
+    if child.is_null() {
+        return;
+    }
+    (*child).allow_next_navigation = 1;
+    webkit_web_view_load_uri(mem::transmute((*child).webview), uri);
+}
+
+unsafe fn webview_create_child(
+    parent: *mut WebView,
+    uri: *const c_char,
+    visible: c_int,
+    related_view: *mut WebKitWebView,
+) -> *mut WebView {
+    let child = webview_create(
+        gtk_window_get_title(mem::transmute((*parent).window)),
+        uri,
+        (*parent).width,
+        (*parent).height,
+        (*parent).resizable,
+        (*parent).debug,
+        (*parent).frameless,
+        visible,
+        (*parent).min_width,
+        (*parent).min_height,
+        0,
+        (*parent).external_invoke_cb,
+        (*parent).userdata,
+        related_view,
+    );
+    if child.is_null() {
+        return child;
+    }
+    (*child).load_cb = (*parent).load_cb;
+    (*child).navigation_cb = (*parent).navigation_cb;
+    (*child).new_window_cb = (*parent).new_window_cb;
+    (*child).schemes = (*parent).schemes.clone();
+    (*child).allowed_dir = (*parent).allowed_dir.clone();
+    #[cfg(feature = "webkit2gtk-2-24")]
+    if !(*parent).file_filter.is_null() {
+        webview_set_file_filter(child, (*parent).file_filter);
+    }
+    (*child).is_child = 1;
+    (*child).parent = parent;
+    for &script in &(*parent).user_scripts {
+        webkit_user_content_manager_add_script((*child).content_manager, script);
+        (*child).user_scripts.push(webkit_user_script_ref(script));
+    }
+    for &(id, style_sheet) in &(*parent).style_sheets {
+        webkit_user_content_manager_add_style_sheet((*child).content_manager, style_sheet);
+        (*child)
+            .style_sheets
+            .push((id, webkit_user_style_sheet_ref(style_sheet)));
+    }
+    (*child).next_style_sheet_id = (*parent).next_style_sheet_id;
+    (*parent).children.push(child);
+    child
+}
+
+unsafe extern "C" fn webview_create_cb(
+    webview: *mut WebKitWebView,
+    action: *mut WebKitNavigationAction,
+    arg: gpointer,
+) -> *mut GtkWidget {
+    let w: *mut WebView = mem::transmute(arg);
+    let uri = webkit_uri_request_get_uri(webkit_navigation_action_get_request(action));
+    // The window stays hidden until WebKit knows the features the page asked for.
+    let child = webview_create_child(w, uri, 0, webview);
+    if child.is_null() {
+        return ptr::null_mut();
+    }
+    (*child).requested_uri = Some(CStr::from_ptr(uri).to_owned());
+    (*child).waiting_to_show = 1;
+    g_signal_connect_data(
+        mem::transmute((*child).webview),
+        CStr::from_bytes_with_nul_unchecked(b"ready-to-show\0").as_ptr(),
+        Some(mem::transmute(webview_ready_to_show_cb as *const ())),
+        mem::transmute(child),
+        None,
+        0,
+    );
+    g_signal_connect_data(
+        mem::transmute((*child).webview),
+        CStr::from_bytes_with_nul_unchecked(b"close\0").as_ptr(),
+        Some(mem::transmute(webview_close_cb as *const ())),
+        mem::transmute(child),
+        None,
+        0,
+    );
+    (*child).webview
+}
+
+unsafe extern "C" fn webview_ready_to_show_cb(webview: *mut WebKitWebView, arg: gpointer) {
+    let w: *mut WebView = mem::transmute(arg);
+    let parent = (*w).parent;
+    (*w).waiting_to_show = 0;
+    let decision = mem::replace(&mut (*w).deferred_decision, ptr::null_mut());
+    let uri = (*w).requested_uri.take().unwrap_or_default();
+
+    let properties = webkit_web_view_get_window_properties(webview);
+    let mut geometry: GdkRectangle = mem::zeroed();
+    webkit_window_properties_get_geometry(properties, &mut geometry);
+    // WebKitGTK reports what the page didn't specify as 0, so a position of 0, 0 can't be told
+    // apart from none.
+    let features = CWindowFeatures {
+        has_position: (geometry.x != 0 || geometry.y != 0) as c_int,
+        x: geometry.x,
+        y: geometry.y,
+        width: geometry.width.max(0),
+        height: geometry.height.max(0),
+        resizable: webkit_window_properties_get_resizable(properties),
+        fullscreen: webkit_window_properties_get_fullscreen(properties),
+    };
+    let mut config = CWindowConfig {
+        title: gtk_window_get_title(mem::transmute((*w).window)),
+        width: if features.width > 0 {
+            features.width
+        } else {
+            (*w).width
+        },
+        height: if features.height > 0 {
+            features.height
+        } else {
+            (*w).height
+        },
+        resizable: features.resizable,
+        fullscreen: features.fullscreen,
+    };
+    let policy = match (*w).new_window_cb {
+        // The opener may have been closed in the meantime.
+        _ if parent.is_null() => WEBVIEW_POLICY_DENY,
+        Some(new_window_cb) => new_window_cb(parent, uri.as_ptr(), &features, &mut config),
+        None => WEBVIEW_POLICY_ALLOW,
+    };
+
+    if policy != WEBVIEW_POLICY_ALLOW {
+        if policy == WEBVIEW_POLICY_OPEN_EXTERNALLY && !uri.as_bytes().is_empty() {
+            gtk_show_uri(
+                ptr::null_mut(),
+                uri.as_ptr(),
+                GDK_CURRENT_TIME as u32,
+                ptr::null_mut(),
+            );
+        }
+        if !decision.is_null() {
+            webkit_policy_decision_ignore(decision);
+            g_object_unref(mem::transmute(decision));
+        }
+        gtk_widget_destroy((*w).window);
+        return;
+    }
+
+    let window = (*w).window;
+    if !config.title.is_null() {
+        gtk_window_set_title(mem::transmute(window), config.title);
+    }
+    (*w).width = config.width;
+    (*w).height = config.height;
+    (*w).resizable = config.resizable;
+    gtk_window_set_resizable(mem::transmute(window), config.resizable);
+    if config.resizable > 0 {
+        gtk_window_set_default_size(mem::transmute(window), config.width, config.height);
+    } else {
+        gtk_widget_set_size_request(window, config.width, config.height);
+    }
+    if config.fullscreen > 0 {
+        gtk_window_fullscreen(mem::transmute(window));
+    }
+    (*w).visible = 1;
+    gtk_widget_show_all(window);
+
+    // The navigation handler has already been asked about the new window.
+    if !decision.is_null() {
+        webkit_policy_decision_use(decision);
+        g_object_unref(mem::transmute(decision));
+    } else if !uri.as_bytes().is_empty() {
+        (*w).allow_next_navigation = 1;
+    }
+}
+
+unsafe extern "C" fn webview_close_cb(_webview: *mut WebKitWebView, arg: gpointer) {
+    // A page can only close windows it opened itself.
+    let w: *mut WebView = mem::transmute(arg);
+    gtk_widget_destroy((*w).window);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_add_user_script(
+    webview: *mut WebView,
+    js: *const c_char,
+    inject_at: c_int,
+    all_frames: c_int,
+) -> c_int {
+    let script = webkit_user_script_new(
+        js,
+        if all_frames != 0 {
+            WEBKIT_USER_CONTENT_INJECT_ALL_FRAMES
+        } else {
+            WEBKIT_USER_CONTENT_INJECT_TOP_FRAME
+        },
+        // Matches the order of `webview_inject_at`.
+        inject_at as WebKitUserScriptInjectionTime,
+        ptr::null(),
+        ptr::null(),
+    );
+    webkit_user_content_manager_add_script((*webview).content_manager, script);
+    (*webview).user_scripts.push(script);
+    0
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_add_style_sheet(
+    webview: *mut WebView,
+    css: *const c_char,
+    level: c_int,
+) -> c_int {
+    let style_sheet = webkit_user_style_sheet_new(
+        css,
+        WEBKIT_USER_CONTENT_INJECT_ALL_FRAMES,
+        // Matches the order of `webview_style_level`.
+        level as WebKitUserStyleLevel,
+        ptr::null(),
+        ptr::null(),
+    );
+    webkit_user_content_manager_add_style_sheet((*webview).content_manager, style_sheet);
+
+    let id = (*webview).next_style_sheet_id;
+    (*webview).next_style_sheet_id += 1;
+    (*webview).style_sheets.push((id, style_sheet));
+    id
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_remove_style_sheet(webview: *mut WebView, id: c_int) {
+    let style_sheets = &mut (*webview).style_sheets;
+    let index = match style_sheets.iter().position(|&(i, _)| i == id) {
+        Some(index) => index,
+        None => return,
+    };
+    webkit_user_style_sheet_unref(style_sheets.remove(index).1);
+
+    // WebKitGTK before 2.32 can only remove all style sheets at once, so re-add the others.
+    webkit_user_content_manager_remove_all_style_sheets((*webview).content_manager);
+    for &(_, style_sheet) in style_sheets.iter() {
+        webkit_user_content_manager_add_style_sheet((*webview).content_manager, style_sheet);
+    }
+}
+
+thread_local! {
+    // Schemes can only be registered once per web context, which all views share. Requests are
+    // still answered by the handler of the view that made them, so several views can register
+    // the same scheme with different handlers.
+    static REGISTERED_SCHEMES: RefCell<HashSet<CString>> = RefCell::new(HashSet::new());
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_register_scheme(
+    webview: *mut WebView,
+    scheme: *const c_char,
+    scheme_cb: SchemeCallback,
+) -> c_int {
+    let scheme = CStr::from_ptr(scheme).to_owned();
+    let is_new = REGISTERED_SCHEMES.with(|schemes| schemes.borrow_mut().insert(scheme.clone()));
+    if is_new {
+        let context = webkit_web_context_get_default();
+        webkit_web_context_register_uri_scheme(
+            context,
+            scheme.as_ptr(),
+            Some(webview_scheme_request_cb),
+            ptr::null_mut(),
+            None,
+        );
+        // Treat the app's own content like https, so it can use fetch and secure APIs.
+        let security_manager = webkit_web_context_get_security_manager(context);
+        webkit_security_manager_register_uri_scheme_as_secure(security_manager, scheme.as_ptr());
+        webkit_security_manager_register_uri_scheme_as_cors_enabled(
+            security_manager,
+            scheme.as_ptr(),
+        );
+    }
+    let schemes = &mut (*webview).schemes;
+    schemes.retain(|(s, _)| *s != scheme);
+    schemes.push((scheme, scheme_cb));
+    0
+}
+
+unsafe extern "C" fn webview_scheme_request_cb(
+    request: *mut WebKitURISchemeRequest,
+    _userdata: gpointer,
+) {
+    let view = webkit_uri_scheme_request_get_web_view(request);
+    let webview: *mut WebView = if view.is_null() {
+        ptr::null_mut()
+    } else {
+        mem::transmute(g_object_get_data(
+            mem::transmute(view),
+            CStr::from_bytes_with_nul_unchecked(b"webview\0").as_ptr(),
+        ))
+    };
+    let scheme = CStr::from_ptr(webkit_uri_scheme_request_get_scheme(request));
+    let scheme_cb = if webview.is_null() {
+        None
+    } else {
+        (*webview)
+            .schemes
+            .iter()
+            .find(|(s, _)| s.as_c_str() == scheme)
+            .map(|&(_, scheme_cb)| scheme_cb)
+    };
+    match scheme_cb {
+        Some(scheme_cb) => scheme_cb(webview, mem::transmute(request)),
+        None => webview_scheme_request_fail(request, 0, "No handler registered for this scheme"),
+    }
+}
+
+unsafe fn webview_scheme_request_fail(
+    request: *mut WebKitURISchemeRequest,
+    code: c_int,
+    message: &str,
+) {
+    let message = CString::new(message).unwrap();
+    let error = g_error_new_literal(
+        g_quark_from_static_string(
+            CStr::from_bytes_with_nul_unchecked(b"webview-scheme-error\0").as_ptr(),
+        ),
+        code,
+        message.as_ptr(),
+    );
+    webkit_uri_scheme_request_finish_error(request, error);
+    g_error_free(error);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_uri(request: *mut c_void) -> *const c_char {
+    webkit_uri_scheme_request_get_uri(mem::transmute(request))
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_scheme(request: *mut c_void) -> *const c_char {
+    webkit_uri_scheme_request_get_scheme(mem::transmute(request))
+}
+
+#[cfg(feature = "webkit2gtk-2-36")]
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_method(request: *mut c_void) -> *const c_char {
+    let method = webkit_uri_scheme_request_get_http_method(mem::transmute(request));
+    if method.is_null() {
+        CStr::from_bytes_with_nul_unchecked(b"GET\0").as_ptr()
+    } else {
+        method
+    }
+}
+
+#[cfg(not(feature = "webkit2gtk-2-36"))]
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_method(_request: *mut c_void) -> *const c_char {
+    CStr::from_bytes_with_nul_unchecked(b"GET\0").as_ptr()
+}
+
+#[cfg(feature = "webkit2gtk-2-36")]
+struct HeaderCallbackArg {
+    cb: HeaderCallback,
+    arg: *mut c_void,
+}
+
+#[cfg(feature = "webkit2gtk-2-36")]
+unsafe extern "C" fn webview_scheme_header_cb(
+    name: *const c_char,
+    value: *const c_char,
+    arg: gpointer,
+) {
+    let arg = &*(arg as *const HeaderCallbackArg);
+    (arg.cb)(arg.arg, name, value);
+}
+
+#[cfg(feature = "webkit2gtk-2-36")]
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_headers(
+    request: *mut c_void,
+    cb: HeaderCallback,
+    arg: *mut c_void,
+) {
+    let headers = webkit_uri_scheme_request_get_http_headers(mem::transmute(request));
+    if !headers.is_null() {
+        let mut arg = HeaderCallbackArg { cb, arg };
+        soup_message_headers_foreach(
+            headers,
+            Some(webview_scheme_header_cb),
+            &mut arg as *mut HeaderCallbackArg as gpointer,
+        );
+    }
+}
+
+#[cfg(not(feature = "webkit2gtk-2-36"))]
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_headers(
+    _request: *mut c_void,
+    _cb: HeaderCallback,
+    _arg: *mut c_void,
+) {
+}
+
+#[cfg(feature = "webkit2gtk-2-40")]
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_body(
+    request: *mut c_void,
+    cb: BodyCallback,
+    arg: *mut c_void,
+) {
+    let body = webkit_uri_scheme_request_get_http_body(mem::transmute(request));
+    if body.is_null() {
+        return;
+    }
+    let mut buf = [0u8; 16 * 1024];
+    loop {
+        let n = gio_sys::g_input_stream_read(
+            body,
+            buf.as_mut_ptr(),
+            buf.len(),
+            ptr::null_mut(),
+            ptr::null_mut(),
+        );
+        if n <= 0 {
+            break;
+        }
+        cb(arg, buf.as_ptr() as *const c_void, n as usize);
+    }
+    g_object_unref(mem::transmute(body));
+}
+
+#[cfg(not(feature = "webkit2gtk-2-40"))]
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_get_body(
+    _request: *mut c_void,
+    _cb: BodyCallback,
+    _arg: *mut c_void,
+) {
+}
+
+#[cfg(feature = "webkit2gtk-2-36")]
+unsafe fn webview_scheme_request_finish_with(
+    request: *mut c_void,
+    status: c_int,
+    mime: *const c_char,
+    header_names: *const *const c_char,
+    header_values: *const *const c_char,
+    header_count: c_int,
+    stream: *mut GInputStream,
+    length: i64,
+) -> c_int {
+    let response = webkit_uri_scheme_response_new(stream, length);
+    webkit_uri_scheme_response_set_status(response, status as c_uint, ptr::null());
+    webkit_uri_scheme_response_set_content_type(response, mime);
+    let headers = soup_message_headers_new(SOUP_MESSAGE_HEADERS_RESPONSE);
+    for i in 0..header_count as isize {
+        soup_message_headers_append(headers, *header_names.offset(i), *header_values.offset(i));
+    }
+    webkit_uri_scheme_response_set_http_headers(response, headers);
+    webkit_uri_scheme_request_finish_with_response(mem::transmute(request), response);
+    g_object_unref(mem::transmute(response));
+    g_object_unref(mem::transmute(stream));
+    0
+}
+
+#[cfg(not(feature = "webkit2gtk-2-36"))]
+unsafe fn webview_scheme_request_finish_with(
+    request: *mut c_void,
+    status: c_int,
+    mime: *const c_char,
+    _header_names: *const *const c_char,
+    _header_values: *const *const c_char,
+    header_count: c_int,
+    stream: *mut GInputStream,
+    length: i64,
+) -> c_int {
+    // Before WebKitGTK 2.36 only the body and its MIME type can be set, so error statuses fail
+    // the request instead and anything else that can't be passed on is reported.
+    let request: *mut WebKitURISchemeRequest = mem::transmute(request);
+    if status >= 400 {
+        webview_scheme_request_fail(request, status, &format!("HTTP status {}", status));
+    } else {
+        webkit_uri_scheme_request_finish(request, stream, length, mime);
+    }
+    g_object_unref(mem::transmute(stream));
+    if (status == 200 || status >= 400) && header_count == 0 {
+        0
+    } else {
+        -1
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_finish(
+    request: *mut c_void,
+    status: c_int,
+    mime: *const c_char,
+    header_names: *const *const c_char,
+    header_values: *const *const c_char,
+    header_count: c_int,
+    data: *const c_void,
+    len: usize,
+) -> c_int {
+    let copy = g_malloc(len);
+    if len > 0 {
+        ptr::copy_nonoverlapping(data as *const u8, copy as *mut u8, len);
+    }
+    let stream = g_memory_input_stream_new_from_data(copy as *mut u8, len as isize, Some(g_free));
+    webview_scheme_request_finish_with(
+        request,
+        status,
+        mime,
+        header_names,
+        header_values,
+        header_count,
+        stream,
+        len as i64,
+    )
+}
+
+// A `GInputStream` reading a streamed body. GIO runs the blocking reads of WebKit's async
+// requests on its worker threads, so a slow reader doesn't block the UI and WebKit can start
+// consuming a body before it is complete.
+#[repr(C)]
+struct SchemeStream {
+    parent: GInputStream,
+    read: Option<ReadFn>,
+    close: Option<CloseFn>,
+    arg: *mut c_void,
+}
+
+unsafe extern "C" fn webview_scheme_stream_read(
+    stream: *mut GInputStream,
+    buf: *mut c_void,
+    len: usize,
+    _cancellable: *mut GCancellable,
+    error: *mut *mut GError,
+) -> isize {
+    let stream = stream as *mut SchemeStream;
+    let n = match (*stream).read {
+        Some(read) => read((*stream).arg, buf as *mut c_char, len),
+        None => 0,
+    };
+    if n < 0 {
+        // Fails the response instead of cutting its body short.
+        g_set_error_literal(
+            error,
+            g_io_error_quark(),
+            G_IO_ERROR_FAILED,
+            CStr::from_bytes_with_nul_unchecked(b"Reading the response body failed\0").as_ptr(),
+        );
+        return -1;
+    }
+    n as isize
+}
+
+unsafe extern "C" fn webview_scheme_stream_close(
+    stream: *mut GInputStream,
+    _cancellable: *mut GCancellable,
+    _error: *mut *mut GError,
+) -> gboolean {
+    let stream = stream as *mut SchemeStream;
+    (*stream).read = None;
+    if let Some(close) = (*stream).close.take() {
+        close((*stream).arg);
+    }
+    GTRUE
+}
+
+unsafe extern "C" fn webview_scheme_stream_class_init(class: gpointer, _data: gpointer) {
+    let class = class as *mut GInputStreamClass;
+    (*class).read_fn = Some(webview_scheme_stream_read);
+    (*class).close_fn = Some(webview_scheme_stream_close);
+}
+
+fn webview_scheme_stream_get_type() -> GType {
+    static REGISTER: Once = Once::new();
+    static mut TYPE: GType = 0;
+    unsafe {
+        REGISTER.call_once(|| {
+            TYPE = g_type_register_static_simple(
+                g_input_stream_get_type(),
+                CStr::from_bytes_with_nul_unchecked(b"WebviewSchemeStream\0").as_ptr(),
+                mem::size_of::<GInputStreamClass>() as c_uint,
+                Some(webview_scheme_stream_class_init),
+                mem::size_of::<SchemeStream>() as c_uint,
+                None,
+                0,
+            );
+        });
+        TYPE
+    }
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_scheme_request_finish_stream(
+    request: *mut c_void,
+    status: c_int,
+    mime: *const c_char,
+    header_names: *const *const c_char,
+    header_values: *const *const c_char,
+    header_count: c_int,
+    length: i64,
+    read: ReadFn,
+    close: CloseFn,
+    arg: *mut c_void,
+) -> c_int {
+    // Disposing the stream closes it, which calls `close` once WebKit is done with the body.
+    let stream = g_object_new(webview_scheme_stream_get_type(), ptr::null()) as *mut SchemeStream;
+    (*stream).read = Some(read);
+    (*stream).close = Some(close);
+    (*stream).arg = arg;
+    webview_scheme_request_finish_with(
+        request,
+        status,
+        mime,
+        header_names,
+        header_values,
+        header_count,
+        stream as *mut GInputStream,
+        length,
+    )
+}
+
+unsafe extern "C" fn webview_eval_finished(
+    _object: *mut GObject,
+    _result: *mut GAsyncResult,
+    userdata: gpointer,
+) {
+    let webview: *mut WebView = mem::transmute(userdata);
+    (*webview).js_busy = 0;
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_eval(webview: *mut WebView, js: *const c_char) -> c_int {
+    while (*webview).ready == 0 {
+        g_main_context_iteration(ptr::null_mut(), GTRUE);
+    }
+
+    (*webview).js_busy = 1;
+
+    webkit_web_view_run_javascript(
+        mem::transmute((*webview).webview),
+        js,
+        ptr::null_mut(),
+        Some(webview_eval_finished),
+        mem::transmute(webview),
+    );
+
+    while (*webview).js_busy == 1 {
+        g_main_context_iteration(ptr::null_mut(), GTRUE);
+    }
+
+    0
+}
+
+struct EvalWithResult {
+    cb: EvalResultCallback,
+    arg: *mut c_void,
+}
+
+unsafe fn js_string_to_cstring(js: JSStringRef) -> CString {
+    let n = JSStringGetMaximumUTF8CStringSize(js);
+    let mut s = vec![0u8; n];
+    JSStringGetUTF8CString(js, s.as_mut_ptr() as *mut c_char, n);
+    JSStringRelease(js);
+    CStr::from_ptr(s.as_ptr() as *const c_char).to_owned()
+}
+
+unsafe fn js_exception_to_cstring(context: JSGlobalContextRef, exception: JSValueRef) -> CString {
+    let message = JSValueToStringCopy(context, exception, ptr::null_mut());
+    if message.is_null() {
+        CString::new("Unknown exception").unwrap()
+    } else {
+        js_string_to_cstring(message)
+    }
+}
+
+unsafe extern "C" fn webview_eval_with_result_finished(
+    object: *mut GObject,
+    result: *mut GAsyncResult,
+    userdata: gpointer,
+) {
+    let eval = Box::from_raw(userdata as *mut EvalWithResult);
+    let mut error = ptr::null_mut();
+    let js_result =
+        webkit_web_view_run_javascript_finish(mem::transmute(object), result, &mut error);
+
+    let mut json = None;
+    let mut exception = None;
+    if js_result.is_null() {
+        exception = Some(CStr::from_ptr((*error).message).to_owned());
+        g_error_free(error);
+    } else {
+        let context = webkit_javascript_result_get_global_context(js_result);
+        let value = webkit_javascript_result_get_value(js_result);
+        // `undefined` has no JSON representation and is reported without one.
+        if JSValueIsUndefined(context, value) == GFALSE {
+            let mut stringify_exception: JSValueRef = ptr::null_mut();
+            let string = JSValueCreateJSONString(context, value, 0, &mut stringify_exception);
+            if !stringify_exception.is_null() {
+                // E.g. a cyclic object, or a `toJSON()` which threw.
+                exception = Some(js_exception_to_cstring(context, stringify_exception));
+            } else if !string.is_null() {
+                json = Some(js_string_to_cstring(string));
+            }
+        }
+        webkit_javascript_result_unref(js_result);
+    }
+
+    (eval.cb)(
+        eval.arg,
+        json.as_ref().map_or(ptr::null(), |json| json.as_ptr()),
+        exception
+            .as_ref()
+            .map_or(ptr::null(), |exception| exception.as_ptr()),
+    );
+}
+
+unsafe fn webview_run_javascript_with_result(
+    webview: *mut WebView,
+    js: *const c_char,
+    cb: EvalResultCallback,
+    arg: *mut c_void,
+) {
+    webkit_web_view_run_javascript(
+        mem::transmute((*webview).webview),
+        js,
+        ptr::null_mut(),
+        Some(webview_eval_with_result_finished),
+        Box::into_raw(Box::new(EvalWithResult { cb, arg })) as gpointer,
+    );
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_eval_with_result(
+    webview: *mut WebView,
+    js: *const c_char,
+    cb: EvalResultCallback,
+    arg: *mut c_void,
+) -> c_int {
+    // Unlike `webview_eval`, this doesn't iterate the main loop, so handlers never run while the
+    // caller waits. Until the page has loaded, the code is queued instead.
+    if (*webview).ready == 0 {
+        (*webview)
+            .pending_evals
+            .push((CStr::from_ptr(js).to_owned(), cb, arg));
+    } else {
+        webview_run_javascript_with_result(webview, js, cb, arg);
+    }
+    0
+}
+
+type DispatchFn = extern "C" fn(webview: *mut WebView, arg: *mut c_void);
+
+#[repr(C)]
+struct DispatchArg {
+    func: DispatchFn,
+    webview: *mut WebView,
+    arg: *mut c_void,
 }
 
 unsafe extern "C" fn webview_dispatch_wrapper(userdata: gpointer) -> gboolean {
@@ -413,9 +2233,79 @@ unsafe extern "C" fn webview_dispatch(webview: *mut WebView, func: DispatchFn, a
     g_async_queue_unlock(queue);
 }
 
+struct Timer {
+    id: c_uint,
+    func: TimerFn,
+    close: CloseFn,
+    webview: *mut WebView,
+    arg: *mut c_void,
+}
+
+unsafe extern "C" fn webview_timer_cb(userdata: gpointer) -> gboolean {
+    let timer = userdata as *mut Timer;
+    if ((*timer).func)((*timer).webview, (*timer).arg) != 0 {
+        GTRUE
+    } else {
+        GFALSE
+    }
+}
+
+unsafe extern "C" fn webview_timer_destroy_cb(userdata: gpointer) {
+    let timer = Box::from_raw(userdata as *mut Timer);
+    (*timer.webview).timers.retain(|&id| id != timer.id);
+    (timer.close)(timer.arg);
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_add_timer(
+    webview: *mut WebView,
+    interval_ms: c_int,
+    func: TimerFn,
+    arg: *mut c_void,
+    close: CloseFn,
+) -> c_int {
+    let timer = Box::into_raw(Box::new(Timer {
+        id: 0,
+        func,
+        close,
+        webview,
+        arg,
+    }));
+    let id = g_timeout_add_full(
+        G_PRIORITY_DEFAULT,
+        interval_ms.max(0) as c_uint,
+        Some(webview_timer_cb),
+        timer as gpointer,
+        Some(webview_timer_destroy_cb),
+    );
+    (*timer).id = id;
+    (*webview).timers.push(id);
+    id as c_int
+}
+
+#[no_mangle]
+unsafe extern "C" fn webview_remove_timer(webview: *mut WebView, id: c_int) {
+    // Removing a timer that has already finished would make GLib complain.
+    if (*webview).timers.contains(&(id as c_uint)) {
+        g_source_remove(id as c_uint);
+    }
+}
+
 #[no_mangle]
 unsafe extern "C" fn webview_destroy_cb(_widget: *mut GtkWidget, arg: gpointer) {
     let webview: *mut WebView = mem::transmute(arg);
+    if !webview.is_null() && (*webview).is_child != 0 {
+        let parent = (*webview).parent;
+        if !parent.is_null() {
+            (*parent).children.retain(|&child| child != webview);
+        }
+        // Handles to the window must fail from now on, not once it is freed.
+        (*webview).closed = 1;
+        webview_close_view_data(webview);
+        // Free once WebKit is done emitting signals for the destroyed view.
+        gdk_threads_add_idle(Some(webview_free_idle), mem::transmute(webview));
+        return;
+    }
     if webview.is_null() || (*webview).hide_instead_of_close == 0 {
         webview_exit(webview);
     }
@@ -11,12 +11,118 @@ mod gtk;
 use std::os::raw::*;
 
 pub enum CWebView {} // opaque type, only used in ffi pointers
+pub enum CSchemeRequest {} // opaque type, only used in ffi pointers
 
 type ErasedExternalInvokeFn = extern "C" fn(webview: *mut CWebView, arg: *const c_char);
 type ErasedDispatchFn = extern "C" fn(webview: *mut CWebView, arg: *mut c_void);
+type ErasedLoadFn =
+    extern "C" fn(webview: *mut CWebView, event: c_int, url: *const c_char, error: *const c_char);
+type ErasedNavigationFn = extern "C" fn(
+    webview: *mut CWebView,
+    url: *const c_char,
+    navigation_type: c_int,
+    target_frame: *const c_char,
+    modifiers: c_int,
+) -> c_int;
+type ErasedNewWindowFn = extern "C" fn(
+    webview: *mut CWebView,
+    url: *const c_char,
+    features: *const CWindowFeatures,
+    config: *mut CWindowConfig,
+) -> c_int;
+type ErasedWindowEventFn =
+    extern "C" fn(webview: *mut CWebView, event: c_int, a: c_int, b: c_int) -> c_int;
+type ErasedSchemeFn = extern "C" fn(webview: *mut CWebView, request: *mut CSchemeRequest);
+type HeaderFn = extern "C" fn(arg: *mut c_void, name: *const c_char, value: *const c_char);
+type BodyFn = extern "C" fn(arg: *mut c_void, data: *const c_void, len: usize);
+type ReadFn = extern "C" fn(arg: *mut c_void, buf: *mut c_char, len: usize) -> i64;
+type CloseFn = extern "C" fn(arg: *mut c_void);
+type TimerFn = extern "C" fn(webview: *mut CWebView, arg: *mut c_void) -> c_int;
+type ErasedEvalResultFn =
+    extern "C" fn(arg: *mut c_void, json: *const c_char, exception: *const c_char);
+
+/// The size and position a page asked for when opening a window. `x` and `y` are only meaningful
+/// if `has_position` is nonzero, `width` and `height` are 0 where the page didn't specify them.
+#[repr(C)]
+pub struct CWindowFeatures {
+    pub has_position: c_int,
+    pub x: c_int,
+    pub y: c_int,
+    pub width: c_int,
+    pub height: c_int,
+    pub resizable: c_int,
+    pub fullscreen: c_int,
+}
+
+/// How a window opened by a page is shown.
+#[repr(C)]
+pub struct CWindowConfig {
+    pub title: *const c_char,
+    pub width: c_int,
+    pub height: c_int,
+    pub resizable: c_int,
+    pub fullscreen: c_int,
+}
+
+/// An area of the screen, e.g. the work area of a monitor.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CRect {
+    pub x: c_int,
+    pub y: c_int,
+    pub width: c_int,
+    pub height: c_int,
+}
+
+pub const WEBVIEW_LOAD_STARTED: c_int = 0;
+pub const WEBVIEW_LOAD_REDIRECTED: c_int = 1;
+pub const WEBVIEW_LOAD_COMMITTED: c_int = 2;
+pub const WEBVIEW_LOAD_FINISHED: c_int = 3;
+pub const WEBVIEW_LOAD_FAILED: c_int = 4;
+
+pub const WEBVIEW_NAVIGATION_LINK_CLICKED: c_int = 0;
+pub const WEBVIEW_NAVIGATION_FORM_SUBMITTED: c_int = 1;
+pub const WEBVIEW_NAVIGATION_BACK_FORWARD: c_int = 2;
+pub const WEBVIEW_NAVIGATION_RELOAD: c_int = 3;
+pub const WEBVIEW_NAVIGATION_FORM_RESUBMITTED: c_int = 4;
+pub const WEBVIEW_NAVIGATION_OTHER: c_int = 5;
+
+pub const WEBVIEW_MODIFIER_SHIFT: c_int = 1;
+pub const WEBVIEW_MODIFIER_CONTROL: c_int = 1 << 1;
+pub const WEBVIEW_MODIFIER_ALT: c_int = 1 << 2;
+pub const WEBVIEW_MODIFIER_META: c_int = 1 << 3;
+
+pub const WEBVIEW_POLICY_ALLOW: c_int = 0;
+pub const WEBVIEW_POLICY_DENY: c_int = 1;
+pub const WEBVIEW_POLICY_OPEN_EXTERNALLY: c_int = 2;
+pub const WEBVIEW_POLICY_OPEN_NEW_WINDOW: c_int = 3;
+
+pub const WEBVIEW_WINDOW_CLOSE_REQUESTED: c_int = 0;
+pub const WEBVIEW_WINDOW_RESIZED: c_int = 1;
+pub const WEBVIEW_WINDOW_MOVED: c_int = 2;
+pub const WEBVIEW_WINDOW_FOCUSED: c_int = 3;
+pub const WEBVIEW_WINDOW_UNFOCUSED: c_int = 4;
+pub const WEBVIEW_WINDOW_MINIMIZED: c_int = 5;
+pub const WEBVIEW_WINDOW_MAXIMIZED: c_int = 6;
+pub const WEBVIEW_WINDOW_RESTORED: c_int = 7;
+pub const WEBVIEW_WINDOW_FULLSCREEN_ENTERED: c_int = 8;
+pub const WEBVIEW_WINDOW_FULLSCREEN_LEFT: c_int = 9;
+
+pub const WEBVIEW_WINDOW_TYPE_NORMAL: c_int = 0;
+pub const WEBVIEW_WINDOW_TYPE_UTILITY: c_int = 1;
+pub const WEBVIEW_WINDOW_TYPE_DIALOG: c_int = 2;
+pub const WEBVIEW_WINDOW_TYPE_SPLASH: c_int = 3;
+pub const WEBVIEW_WINDOW_TYPE_DOCK: c_int = 4;
+
+pub const WEBVIEW_INJECT_AT_DOCUMENT_START: c_int = 0;
+pub const WEBVIEW_INJECT_AT_DOCUMENT_END: c_int = 1;
+
+pub const WEBVIEW_STYLE_LEVEL_USER: c_int = 0;
+pub const WEBVIEW_STYLE_LEVEL_AUTHOR: c_int = 1;
 
 extern "C" {
     pub fn webview_free(this: *mut CWebView);
+    pub fn webview_init() -> c_int;
     pub fn webview_new(
         title: *const c_char,
         url: *const c_char,
@@ -33,17 +139,130 @@ extern "C" {
         userdata: *mut c_void,
     ) -> *mut CWebView;
     pub fn webview_loop(this: *mut CWebView, blocking: c_int) -> c_int;
+    pub fn webview_loop_timeout(this: *mut CWebView, timeout_ms: c_int) -> c_int;
     pub fn webview_exit(this: *mut CWebView);
     pub fn webview_get_user_data(this: *mut CWebView) -> *mut c_void;
     pub fn webview_get_window_handle(this: *mut CWebView) -> *mut c_void;
+    pub fn webview_set_view_data(this: *mut CWebView, data: *mut c_void, free_fn: CloseFn)
+        -> c_int;
+    pub fn webview_get_view_data(this: *mut CWebView) -> *mut c_void;
+    pub fn webview_register_scheme(
+        this: *mut CWebView,
+        scheme: *const c_char,
+        scheme_cb: Option<ErasedSchemeFn>,
+    ) -> c_int;
+    pub fn webview_scheme_request_get_uri(request: *mut CSchemeRequest) -> *const c_char;
+    pub fn webview_scheme_request_get_scheme(request: *mut CSchemeRequest) -> *const c_char;
+    pub fn webview_scheme_request_get_method(request: *mut CSchemeRequest) -> *const c_char;
+    pub fn webview_scheme_request_get_headers(
+        request: *mut CSchemeRequest,
+        cb: HeaderFn,
+        arg: *mut c_void,
+    );
+    pub fn webview_scheme_request_get_body(
+        request: *mut CSchemeRequest,
+        cb: BodyFn,
+        arg: *mut c_void,
+    );
+    pub fn webview_scheme_request_finish(
+        request: *mut CSchemeRequest,
+        status: c_int,
+        mime: *const c_char,
+        header_names: *const *const c_char,
+        header_values: *const *const c_char,
+        header_count: c_int,
+        data: *const c_void,
+        len: usize,
+    ) -> c_int;
+    pub fn webview_scheme_request_finish_stream(
+        request: *mut CSchemeRequest,
+        status: c_int,
+        mime: *const c_char,
+        header_names: *const *const c_char,
+        header_values: *const *const c_char,
+        header_count: c_int,
+        length: i64,
+        read: ReadFn,
+        close: CloseFn,
+        arg: *mut c_void,
+    ) -> c_int;
     pub fn webview_dispatch(this: *mut CWebView, f: Option<ErasedDispatchFn>, arg: *mut c_void);
+    pub fn webview_add_timer(
+        this: *mut CWebView,
+        interval_ms: c_int,
+        f: TimerFn,
+        arg: *mut c_void,
+        close: CloseFn,
+    ) -> c_int;
+    pub fn webview_remove_timer(this: *mut CWebView, id: c_int);
     pub fn webview_eval(this: *mut CWebView, js: *const c_char) -> c_int;
+    pub fn webview_eval_with_result(
+        this: *mut CWebView,
+        js: *const c_char,
+        cb: Option<ErasedEvalResultFn>,
+        arg: *mut c_void,
+    ) -> c_int;
     pub fn webview_set_title(this: *mut CWebView, title: *const c_char);
     pub fn webview_set_fullscreen(this: *mut CWebView, fullscreen: c_int);
     pub fn webview_set_maximized(this: *mut CWebView, maximize: c_int);
     pub fn webview_set_minimized(this: *mut CWebView, minimize: c_int);
     pub fn webview_set_visible(this: *mut CWebView, visible: c_int);
+    pub fn webview_get_size(this: *mut CWebView, width: *mut c_int, height: *mut c_int);
+    pub fn webview_set_size(this: *mut CWebView, width: c_int, height: c_int);
+    pub fn webview_get_position(this: *mut CWebView, x: *mut c_int, y: *mut c_int);
+    pub fn webview_set_position(this: *mut CWebView, x: c_int, y: c_int);
+    pub fn webview_center(this: *mut CWebView);
+    pub fn webview_set_max_size(this: *mut CWebView, width: c_int, height: c_int);
+    pub fn webview_set_aspect_ratio(this: *mut CWebView, ratio: c_double);
+    pub fn webview_get_monitors(
+        this: *mut CWebView,
+        work_areas: *mut CRect,
+        max_count: c_int,
+    ) -> c_int;
+    pub fn webview_set_icon(
+        this: *mut CWebView,
+        data: *const u8,
+        len: usize,
+        width: c_int,
+        height: c_int,
+    ) -> c_int;
+    pub fn webview_set_always_on_top(this: *mut CWebView, always_on_top: c_int);
+    pub fn webview_set_skip_taskbar(this: *mut CWebView, skip: c_int);
+    pub fn webview_set_skip_pager(this: *mut CWebView, skip: c_int);
+    pub fn webview_set_sticky(this: *mut CWebView, sticky: c_int);
+    pub fn webview_set_window_type(this: *mut CWebView, window_type: c_int);
+    pub fn webview_set_transparent(this: *mut CWebView, transparent: c_int);
     pub fn webview_set_color(this: *mut CWebView, red: u8, green: u8, blue: u8, alpha: u8);
     pub fn webview_set_zoom_level(this: *mut CWebView, percentage: c_double);
-    pub fn webview_set_html(this: *mut CWebView, html: *const c_char);
+    pub fn webview_get_zoom_level(this: *mut CWebView) -> c_double;
+    pub fn webview_set_html(this: *mut CWebView, html: *const c_char, base_url: *const c_char);
+    pub fn webview_navigate(this: *mut CWebView, url: *const c_char);
+    pub fn webview_load_file(this: *mut CWebView, url: *const c_char, allowed_dir: *const c_char);
+    pub fn webview_reload(this: *mut CWebView, bypass_cache: c_int);
+    pub fn webview_go_back(this: *mut CWebView);
+    pub fn webview_go_forward(this: *mut CWebView);
+    pub fn webview_can_go_back(this: *mut CWebView) -> c_int;
+    pub fn webview_can_go_forward(this: *mut CWebView) -> c_int;
+    pub fn webview_stop_loading(this: *mut CWebView);
+    pub fn webview_get_url(this: *mut CWebView) -> *const c_char;
+    pub fn webview_get_page_title(this: *mut CWebView) -> *const c_char;
+    pub fn webview_set_load_cb(this: *mut CWebView, load_cb: Option<ErasedLoadFn>);
+    pub fn webview_get_load_progress(this: *mut CWebView) -> c_double;
+    pub fn webview_set_navigation_cb(
+        this: *mut CWebView,
+        navigation_cb: Option<ErasedNavigationFn>,
+    );
+    pub fn webview_set_new_window_cb(this: *mut CWebView, new_window_cb: Option<ErasedNewWindowFn>);
+    pub fn webview_set_window_event_cb(
+        this: *mut CWebView,
+        window_event_cb: Option<ErasedWindowEventFn>,
+    );
+    pub fn webview_add_user_script(
+        this: *mut CWebView,
+        js: *const c_char,
+        inject_at: c_int,
+        all_frames: c_int,
+    ) -> c_int;
+    pub fn webview_add_style_sheet(this: *mut CWebView, css: *const c_char, level: c_int) -> c_int;
+    pub fn webview_remove_style_sheet(this: *mut CWebView, id: c_int);
 }
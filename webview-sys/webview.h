@@ -17,34 +17,282 @@ extern "C" {
 #include <stdio.h>
 
 typedef void* webview_t;
+
+// The size and position a page asked for when opening a window. x and y are
+// only meaningful if has_position is nonzero, as any position is valid. width
+// and height are 0 where the page didn't specify them.
+struct webview_window_features {
+  int has_position;
+  int x;
+  int y;
+  int width;
+  int height;
+  int resizable;
+  int fullscreen;
+};
+
+// How a window opened by a page is shown. title must stay valid until the
+// new window callback is called again.
+struct webview_window_config {
+  const char *title;
+  int width;
+  int height;
+  int resizable;
+  int fullscreen;
+};
+
+struct webview_rect {
+  int x;
+  int y;
+  int width;
+  int height;
+};
+
 typedef void (*webview_external_invoke_cb_t)(webview_t w, const char *arg);
 typedef void (*webview_dispatch_fn)(webview_t w, void *arg);
+typedef void (*webview_load_cb_t)(webview_t w, int event, const char *url,
+                                  const char *error);
+typedef void (*webview_eval_result_cb_t)(void *arg, const char *json,
+                                         const char *exception);
+typedef int (*webview_navigation_cb_t)(webview_t w, const char *url, int type,
+                                       const char *target_frame,
+                                       int modifiers);
+// config is filled with defaults and may be changed. Returns a webview_policy,
+// WEBVIEW_POLICY_ALLOW opens the window as configured.
+typedef int (*webview_new_window_cb_t)(
+    webview_t w, const char *url,
+    const struct webview_window_features *features,
+    struct webview_window_config *config);
+// a and b are the new width and height for WEBVIEW_WINDOW_RESIZED, and the new
+// position for WEBVIEW_WINDOW_MOVED. Returning nonzero for
+// WEBVIEW_WINDOW_CLOSE_REQUESTED keeps the window open, the return value is
+// ignored for other events. Not called for windows opened by the page.
+typedef int (*webview_window_event_cb_t)(webview_t w, int event, int a, int b);
+typedef void* webview_scheme_request_t;
+typedef void (*webview_scheme_cb_t)(webview_t w,
+                                    webview_scheme_request_t request);
+typedef void (*webview_header_cb_t)(void *arg, const char *name,
+                                    const char *value);
+typedef void (*webview_body_cb_t)(void *arg, const void *data, size_t len);
+// Returns the number of bytes read into buf, 0 at the end of the stream or -1
+// on error. Called on a background thread.
+typedef int64_t (*webview_read_fn)(void *arg, char *buf, size_t len);
+typedef void (*webview_close_fn)(void *arg);
+// Returns nonzero to be called again after the next interval.
+typedef int (*webview_timer_fn)(webview_t w, void *arg);
 
 WEBVIEW_API void webview_run(webview_t w);
 WEBVIEW_API int webview_loop(webview_t w, int blocking);
+// Like a blocking webview_loop, but waits at most timeout_ms for an event.
+WEBVIEW_API int webview_loop_timeout(webview_t w, int timeout_ms);
 WEBVIEW_API int webview_eval(webview_t w, const char *js);
+// Calls cb from the event loop with the completion value of js as JSON, or
+// with the exception it threw. json is NULL if the value is undefined or has no
+// JSON representation. Does not wait for js to run; cb is called exactly once,
+// with an exception if the webview is freed first. Returns -1 without calling
+// cb if the backend doesn't support this.
+WEBVIEW_API int webview_eval_with_result(webview_t w, const char *js,
+                                         webview_eval_result_cb_t cb,
+                                         void *arg);
 WEBVIEW_API void webview_set_title(webview_t w, const char *title);
 WEBVIEW_API void webview_set_fullscreen(webview_t w, int fullscreen);
 WEBVIEW_API void webview_set_maximized(webview_t w, int maximize);
 WEBVIEW_API void webview_set_minimized(webview_t w, int minimize);
 WEBVIEW_API void webview_set_visible(webview_t w, int minimize);
+// The size is that of the content, the position that of the window frame.
+WEBVIEW_API void webview_get_size(webview_t w, int *width, int *height);
+WEBVIEW_API void webview_set_size(webview_t w, int width, int height);
+WEBVIEW_API void webview_get_position(webview_t w, int *x, int *y);
+WEBVIEW_API void webview_set_position(webview_t w, int x, int y);
+WEBVIEW_API void webview_center(webview_t w);
+// A negative width or height leaves that dimension of the maximum unlimited.
+WEBVIEW_API void webview_set_max_size(webview_t w, int width, int height);
+// ratio is the width divided by the height, 0 removes the constraint.
+WEBVIEW_API void webview_set_aspect_ratio(webview_t w, double ratio);
+// Fills work_areas with the parts of up to max_count monitors that aren't taken
+// by panels and returns the number of monitors, 0 if they are unknown.
+WEBVIEW_API int webview_get_monitors(webview_t w,
+                                     struct webview_rect *work_areas,
+                                     int max_count);
+// data is the contents of a PNG or ICO file, or width * height RGBA pixels if
+// width and height are positive. Returns -1 if it can't be decoded.
+WEBVIEW_API int webview_set_icon(webview_t w, const uint8_t *data, size_t len,
+                                 int width, int height);
+WEBVIEW_API void webview_set_always_on_top(webview_t w, int always_on_top);
+WEBVIEW_API void webview_set_skip_taskbar(webview_t w, int skip);
+WEBVIEW_API void webview_set_skip_pager(webview_t w, int skip);
+// Shows the window on all workspaces.
+WEBVIEW_API void webview_set_sticky(webview_t w, int sticky);
+// type is a webview_window_type. Most window managers only apply it to windows
+// that aren't shown yet, so a shown window is hidden and shown again.
+WEBVIEW_API void webview_set_window_type(webview_t w, int type);
+// Lets the desktop show through where neither the window nor the page paint a
+// background. Must be called before the window is shown.
+WEBVIEW_API void webview_set_transparent(webview_t w, int transparent);
 WEBVIEW_API void webview_set_color(webview_t w, uint8_t r, uint8_t g,
                                    uint8_t b, uint8_t a);
 WEBVIEW_API void webview_set_zoom_level(webview_t w, const double percentage);
-WEBVIEW_API void webview_set_html(webview_t w, const char *html);
+// Returns the zoom level, which the user may have changed since it was set, or
+// -1 if it can't be read.
+WEBVIEW_API double webview_get_zoom_level(webview_t w);
+// base_url may be NULL, relative URLs in the page are resolved against it.
+WEBVIEW_API void webview_set_html(webview_t w, const char *html,
+                                  const char *base_url);
+WEBVIEW_API void webview_navigate(webview_t w, const char *url);
+// Loads url, a file: URL, letting the page read the files in allowed_dir and
+// its subdirectories, but no other local files. On GTK without the
+// webkit2gtk-2-24 feature, only navigations to other files are denied. The load
+// may start after this returns.
+WEBVIEW_API void webview_load_file(webview_t w, const char *url,
+                                   const char *allowed_dir);
+WEBVIEW_API void webview_reload(webview_t w, int bypass_cache);
+WEBVIEW_API void webview_go_back(webview_t w);
+WEBVIEW_API void webview_go_forward(webview_t w);
+WEBVIEW_API int webview_can_go_back(webview_t w);
+WEBVIEW_API int webview_can_go_forward(webview_t w);
+WEBVIEW_API void webview_stop_loading(webview_t w);
+// The returned strings are owned by the webview and only valid until the next
+// call or navigation. NULL if nothing has been loaded yet.
+WEBVIEW_API const char *webview_get_url(webview_t w);
+WEBVIEW_API const char *webview_get_page_title(webview_t w);
+WEBVIEW_API void webview_set_load_cb(webview_t w, webview_load_cb_t load_cb);
+WEBVIEW_API double webview_get_load_progress(webview_t w);
+WEBVIEW_API void webview_set_navigation_cb(webview_t w,
+                                           webview_navigation_cb_t navigation_cb);
+// Called when the page opens a window with window.open or a link with
+// target="_blank". The window shares the web context, callbacks and userdata of
+// w. Without a callback, every such window is opened.
+WEBVIEW_API void webview_set_new_window_cb(webview_t w,
+                                           webview_new_window_cb_t new_window_cb);
+WEBVIEW_API void webview_set_window_event_cb(webview_t w,
+                                             webview_window_event_cb_t window_event_cb);
+WEBVIEW_API int webview_add_user_script(webview_t w, const char *js,
+                                        int inject_at, int all_frames);
+WEBVIEW_API int webview_add_style_sheet(webview_t w, const char *css,
+                                        int level);
+WEBVIEW_API void webview_remove_style_sheet(webview_t w, int id);
+// Requests for URIs with the given scheme are passed to scheme_cb, which must
+// answer each of them with exactly one call to a finish function. Returns -1 if
+// custom schemes are not supported.
+WEBVIEW_API int webview_register_scheme(webview_t w, const char *scheme,
+                                        webview_scheme_cb_t scheme_cb);
+WEBVIEW_API const char *
+webview_scheme_request_get_uri(webview_scheme_request_t request);
+WEBVIEW_API const char *
+webview_scheme_request_get_scheme(webview_scheme_request_t request);
+WEBVIEW_API const char *
+webview_scheme_request_get_method(webview_scheme_request_t request);
+WEBVIEW_API void
+webview_scheme_request_get_headers(webview_scheme_request_t request,
+                                   webview_header_cb_t cb, void *arg);
+WEBVIEW_API void webview_scheme_request_get_body(webview_scheme_request_t request,
+                                                 webview_body_cb_t cb, void *arg);
+WEBVIEW_API int webview_scheme_request_finish(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, const void *data, size_t len);
+// Like webview_scheme_request_finish, but the body is read with read until it
+// returns 0, then close is called. If read returns -1, loading the body fails
+// instead of ending early. length is -1 if unknown.
+//
+// Both finish functions return -1 if the backend couldn't pass on the status or
+// the headers. The request is still answered.
+WEBVIEW_API int webview_scheme_request_finish_stream(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, int64_t length, webview_read_fn read,
+    webview_close_fn close, void *arg);
 WEBVIEW_API void webview_dispatch(webview_t w, webview_dispatch_fn fn,
                                   void *arg);
+// Calls fn on the UI thread after interval_ms, then again as long as it returns
+// nonzero. close is called with arg once the timer is removed. Returns the id
+// of the timer, or -1 if timers are not supported.
+WEBVIEW_API int webview_add_timer(webview_t w, int interval_ms,
+                                  webview_timer_fn fn, void *arg,
+                                  webview_close_fn close);
+WEBVIEW_API void webview_remove_timer(webview_t w, int id);
 WEBVIEW_API void webview_exit(webview_t w);
 WEBVIEW_API void webview_debug(const char *format, ...);
 WEBVIEW_API void webview_print_log(const char *s);
 
 WEBVIEW_API void* webview_get_user_data(webview_t w);
 WEBVIEW_API void* webview_get_window_handle(webview_t w);
+// Attaches data to w, which is passed to free_fn once w is closed: windows
+// opened by the page when they are destroyed, others in webview_free. Returns
+// -1 without attaching it if w is closed already.
+WEBVIEW_API int webview_set_view_data(webview_t w, void *data,
+                                      webview_close_fn free_fn);
+WEBVIEW_API void* webview_get_view_data(webview_t w);
+// Sets up the UI toolkit once per process, webview_new does so if it hasn't
+// been yet. Returns -1 on failure.
+WEBVIEW_API int webview_init(void);
+// url may be NULL to load nothing, e.g. before calling webview_set_html.
 WEBVIEW_API webview_t webview_new(const char* title, const char* url, int width, int height, int resizable, int debug, int frameless, int visible, int min_width, int min_height, int hide_instead_of_close, webview_external_invoke_cb_t external_invoke_cb, void* userdata);
 WEBVIEW_API void webview_free(webview_t w);
 WEBVIEW_API void webview_destroy(webview_t w);
 
-// TODO WEBVIEW_API void webview_navigate(webview_t w, const char* url);
+
+enum webview_load_event {
+  WEBVIEW_LOAD_STARTED,
+  WEBVIEW_LOAD_REDIRECTED,
+  WEBVIEW_LOAD_COMMITTED,
+  WEBVIEW_LOAD_FINISHED,
+  WEBVIEW_LOAD_FAILED,
+};
+
+enum webview_navigation_type {
+  WEBVIEW_NAVIGATION_LINK_CLICKED,
+  WEBVIEW_NAVIGATION_FORM_SUBMITTED,
+  WEBVIEW_NAVIGATION_BACK_FORWARD,
+  WEBVIEW_NAVIGATION_RELOAD,
+  WEBVIEW_NAVIGATION_FORM_RESUBMITTED,
+  WEBVIEW_NAVIGATION_OTHER,
+};
+
+enum webview_modifier {
+  WEBVIEW_MODIFIER_SHIFT = 1 << 0,
+  WEBVIEW_MODIFIER_CONTROL = 1 << 1,
+  WEBVIEW_MODIFIER_ALT = 1 << 2,
+  WEBVIEW_MODIFIER_META = 1 << 3,
+};
+
+enum webview_policy {
+  WEBVIEW_POLICY_ALLOW,
+  WEBVIEW_POLICY_DENY,
+  WEBVIEW_POLICY_OPEN_EXTERNALLY,
+  WEBVIEW_POLICY_OPEN_NEW_WINDOW,
+};
+
+enum webview_window_event {
+  WEBVIEW_WINDOW_CLOSE_REQUESTED,
+  WEBVIEW_WINDOW_RESIZED,
+  WEBVIEW_WINDOW_MOVED,
+  WEBVIEW_WINDOW_FOCUSED,
+  WEBVIEW_WINDOW_UNFOCUSED,
+  WEBVIEW_WINDOW_MINIMIZED,
+  WEBVIEW_WINDOW_MAXIMIZED,
+  WEBVIEW_WINDOW_RESTORED,
+  WEBVIEW_WINDOW_FULLSCREEN_ENTERED,
+  WEBVIEW_WINDOW_FULLSCREEN_LEFT,
+};
+
+enum webview_window_type {
+  WEBVIEW_WINDOW_TYPE_NORMAL,
+  WEBVIEW_WINDOW_TYPE_UTILITY,
+  WEBVIEW_WINDOW_TYPE_DIALOG,
+  WEBVIEW_WINDOW_TYPE_SPLASH,
+  WEBVIEW_WINDOW_TYPE_DOCK,
+};
+
+enum webview_inject_at {
+  WEBVIEW_INJECT_AT_DOCUMENT_START,
+  WEBVIEW_INJECT_AT_DOCUMENT_END,
+};
+
+enum webview_style_level {
+  WEBVIEW_STYLE_LEVEL_USER,
+  WEBVIEW_STYLE_LEVEL_AUTHOR,
+};
 
 struct webview_dispatch_arg {
   webview_dispatch_fn fn;
@@ -27,11 +27,30 @@ struct cocoa_webview {
   webview_external_invoke_cb_t external_invoke_cb;
   struct webview_priv priv;
   void *userdata;
+  void *view_data;
+  webview_close_fn view_data_free;
 };
 
-WEBVIEW_API int webview_init(webview_t w);
+WEBVIEW_API int webview_init_window(webview_t w);
+
+WEBVIEW_API int webview_set_view_data(webview_t w, void *data,
+                                      webview_close_fn free_fn) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  if (wv->view_data_free != NULL) {
+    wv->view_data_free(wv->view_data);
+  }
+  wv->view_data = data;
+  wv->view_data_free = free_fn;
+  return 0;
+}
+
+WEBVIEW_API void* webview_get_view_data(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  return wv->view_data;
+}
 
 WEBVIEW_API void webview_free(webview_t w) {
+  webview_set_view_data(w, NULL, NULL);
 	free(w);
 }
 
@@ -40,6 +59,11 @@ WEBVIEW_API void* webview_get_user_data(webview_t w) {
 	return wv->userdata;
 }
 
+WEBVIEW_API int webview_init(void) {
+  // Cocoa has nothing to set up before the first window.
+  return 0;
+}
+
 WEBVIEW_API webview_t webview_new(
   const char* title, const char* url, 
   int width, int height, int resizable, int debug, int frameless, int visible, int min_width, int min_height, int hide_instead_of_close,
@@ -58,7 +82,7 @@ WEBVIEW_API webview_t webview_new(
   wv->hide_instead_of_close = hide_instead_of_close;
 	wv->external_invoke_cb = external_invoke_cb;
 	wv->userdata = userdata;
-	if (webview_init(wv) != 0) {
+	if (webview_init_window(wv) != 0) {
 		webview_free(wv);
 		return NULL;
 	}
@@ -72,6 +96,9 @@ WEBVIEW_API webview_t webview_new(
 #define NSWindowStyleMaskTitled 1
 #define NSWindowStyleMaskClosable 2
 #define NSWindowStyleMaskFullScreen (1 << 14)
+#define NSWindowCollectionBehaviorCanJoinAllSpaces (1 << 0)
+#define NSNormalWindowLevel 0
+#define NSFloatingWindowLevel 3
 #define NSViewWidthSizable 2
 #define NSViewHeightSizable 16
 #define NSBackingStoreBuffered 2
@@ -85,6 +112,7 @@ WEBVIEW_API webview_t webview_new(
 #define WKNavigationActionPolicyDownload 2
 #define WKNavigationResponsePolicyAllow 1
 #define WKUserScriptInjectionTimeAtDocumentStart 0
+#define WKUserScriptInjectionTimeAtDocumentEnd 1
 #define NSApplicationActivationPolicyRegular 0
 #define NSApplicationDefinedEvent 15
 #define NSWindowStyleMaskBorderless 0
@@ -264,7 +292,7 @@ static void make_nav_policy_decision(id self, SEL cmd, id webView, id response,
   }
 }
 
-WEBVIEW_API int webview_init(webview_t w) {
+WEBVIEW_API int webview_init_window(webview_t w) {
   struct cocoa_webview* wv = (struct cocoa_webview*)w;
   wv->priv.pool = ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSAutoreleasePool"),
                               sel_registerName("new"));
@@ -461,13 +489,14 @@ WEBVIEW_API int webview_init(webview_t w) {
   ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.webview, sel_registerName("setNavigationDelegate:"),
                navDel);
 
-  id nsURL = ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURL"),
-                          sel_registerName("URLWithString:"),
-                          get_nsstring(wv->url == NULL ? "" : wv->url));
+  if (wv->url != NULL) {
+    id nsURL = ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURL"),
+                            sel_registerName("URLWithString:"), get_nsstring(wv->url));
 
-  ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.webview, sel_registerName("loadRequest:"),
-               ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURLRequest"),
-                            sel_registerName("requestWithURL:"), nsURL));
+    ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.webview, sel_registerName("loadRequest:"),
+                 ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURLRequest"),
+                              sel_registerName("requestWithURL:"), nsURL));
+  }
   ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.webview, sel_registerName("setAutoresizesSubviews:"), 1);
   ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.webview, sel_registerName("setAutoresizingMask:"),
                (NSViewWidthSizable | NSViewHeightSizable));
@@ -549,12 +578,7 @@ WEBVIEW_API int webview_init(webview_t w) {
   return 0;
 }
 
-WEBVIEW_API int webview_loop(webview_t w, int blocking) {
-  struct cocoa_webview* wv = (struct cocoa_webview*)w;
-  id until = (blocking ? ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSDate"),
-                                      sel_registerName("distantFuture"))
-                       : ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSDate"),
-                                      sel_registerName("distantPast")));
+static int webview_loop_until(struct cocoa_webview *wv, id until) {
   id app = ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSApplication"),
                    sel_registerName("sharedApplication"));
   id event = ((id(*)(id, SEL, id, id, id, id))objc_msgSend)(
@@ -574,6 +598,23 @@ WEBVIEW_API int webview_loop(webview_t w, int blocking) {
   return wv->priv.should_exit;
 }
 
+WEBVIEW_API int webview_loop(webview_t w, int blocking) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id until = (blocking ? ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSDate"),
+                                      sel_registerName("distantFuture"))
+                       : ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSDate"),
+                                      sel_registerName("distantPast")));
+  return webview_loop_until(wv, until);
+}
+
+WEBVIEW_API int webview_loop_timeout(webview_t w, int timeout_ms) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id until = ((id(*)(id, SEL, double))objc_msgSend)((id)objc_getClass("NSDate"),
+                   sel_registerName("dateWithTimeIntervalSinceNow:"),
+                   timeout_ms / 1000.0);
+  return webview_loop_until(wv, until);
+}
+
 WEBVIEW_API int webview_eval(webview_t w, const char *js) {
   struct cocoa_webview* wv = (struct cocoa_webview*)w;
   ((id(*)(id, SEL, id, id))objc_msgSend)(wv->priv.webview,
@@ -583,6 +624,13 @@ WEBVIEW_API int webview_eval(webview_t w, const char *js) {
   return 0;
 }
 
+WEBVIEW_API int webview_eval_with_result(webview_t w, const char *js,
+                                         webview_eval_result_cb_t cb,
+                                         void *arg) {
+  // Evaluating JavaScript with a result is not supported on Cocoa yet.
+  return -1;
+}
+
 WEBVIEW_API void webview_set_title(webview_t w, const char *title) {
   struct cocoa_webview* wv = (struct cocoa_webview*)w;
   ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.window, sel_registerName("setTitle:"),
@@ -636,6 +684,111 @@ WEBVIEW_API void webview_set_visible(webview_t w, int visible) {
   }
 }
 
+WEBVIEW_API void webview_get_size(webview_t w, int *width, int *height) {
+  // Reading the geometry is not supported on Cocoa yet, this is the last
+  // requested size.
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  *width = wv->width;
+  *height = wv->height;
+}
+
+WEBVIEW_API void webview_set_size(webview_t w, int width, int height) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  wv->width = width;
+  wv->height = height;
+  ((id(*)(id, SEL, CGSize))objc_msgSend)(wv->priv.window, sel_registerName("setContentSize:"), CGSizeMake(width, height));
+}
+
+WEBVIEW_API void webview_get_position(webview_t w, int *x, int *y) {
+  // Reading the geometry is not supported on Cocoa yet.
+  *x = 0;
+  *y = 0;
+}
+
+WEBVIEW_API void webview_set_position(webview_t w, int x, int y) {
+  // Cocoa places windows from the bottom of the screen, which isn't handled yet.
+}
+
+WEBVIEW_API void webview_center(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  ((id(*)(id, SEL))objc_msgSend)(wv->priv.window, sel_registerName("center"));
+}
+
+WEBVIEW_API void webview_set_max_size(webview_t w, int width, int height) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  CGSize size = CGSizeMake(width < 0 ? CGFLOAT_MAX : width,
+                           height < 0 ? CGFLOAT_MAX : height);
+  ((id(*)(id, SEL, CGSize))objc_msgSend)(wv->priv.window, sel_registerName("setContentMaxSize:"), size);
+}
+
+WEBVIEW_API void webview_set_aspect_ratio(webview_t w, double ratio) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  if (ratio > 0) {
+    ((id(*)(id, SEL, CGSize))objc_msgSend)(wv->priv.window, sel_registerName("setContentAspectRatio:"), CGSizeMake(ratio, 1));
+  } else {
+    // Setting resize increments clears the aspect ratio.
+    ((id(*)(id, SEL, CGSize))objc_msgSend)(wv->priv.window, sel_registerName("setContentResizeIncrements:"), CGSizeMake(1, 1));
+  }
+}
+
+WEBVIEW_API int webview_get_monitors(webview_t w,
+                                     struct webview_rect *work_areas,
+                                     int max_count) {
+  // Monitors are not listed on Cocoa yet.
+  return 0;
+}
+
+WEBVIEW_API void webview_set_always_on_top(webview_t w, int always_on_top) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  ((id(*)(id, SEL, long))objc_msgSend)(
+      wv->priv.window, sel_registerName("setLevel:"),
+      always_on_top ? NSFloatingWindowLevel : NSNormalWindowLevel);
+}
+
+WEBVIEW_API void webview_set_skip_taskbar(webview_t w, int skip) {
+  // Windows are never shown in the Dock on their own.
+}
+
+WEBVIEW_API void webview_set_skip_pager(webview_t w, int skip) {
+  // There is no pager on macOS.
+}
+
+WEBVIEW_API void webview_set_sticky(webview_t w, int sticky) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  unsigned long behavior = (unsigned long)((id(*)(id, SEL))objc_msgSend)(
+      wv->priv.window, sel_registerName("collectionBehavior"));
+  if (sticky) {
+    behavior |= NSWindowCollectionBehaviorCanJoinAllSpaces;
+  } else {
+    behavior &= ~NSWindowCollectionBehaviorCanJoinAllSpaces;
+  }
+  ((id(*)(id, SEL, unsigned long))objc_msgSend)(
+      wv->priv.window, sel_registerName("setCollectionBehavior:"), behavior);
+}
+
+WEBVIEW_API void webview_set_window_type(webview_t w, int type) {
+  // Window types are not supported on Cocoa yet.
+}
+
+WEBVIEW_API int webview_set_icon(webview_t w, const uint8_t *data, size_t len,
+                                 int width, int height) {
+  // Windows have no icons on macOS, the icon of the app bundle is shown.
+  return 0;
+}
+
+WEBVIEW_API void webview_set_transparent(webview_t w, int transparent) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id color = transparent
+      ? ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSColor"), sel_registerName("clearColor"))
+      : ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("NSColor"), sel_registerName("windowBackgroundColor"));
+  ((id(*)(id, SEL, BOOL))objc_msgSend)(wv->priv.window, sel_registerName("setOpaque:"), !transparent);
+  ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.window, sel_registerName("setBackgroundColor:"), color);
+  ((id(*)(id, SEL, id, id))objc_msgSend)(
+      wv->priv.webview, sel_registerName("setValue:forKey:"),
+      ((id(*)(id, SEL, BOOL))objc_msgSend)((id)objc_getClass("NSNumber"), sel_registerName("numberWithBool:"), !transparent),
+      get_nsstring("drawsBackground"));
+}
+
 WEBVIEW_API void webview_set_color(webview_t w, uint8_t r, uint8_t g,
                                    uint8_t b, uint8_t a) {
   struct cocoa_webview* wv = (struct cocoa_webview*)w;
@@ -667,10 +820,203 @@ WEBVIEW_API void webview_set_zoom_level(webview_t w, const double percentage) {
     // Ignored on Cocoa
 }
 
-WEBVIEW_API void webview_set_html(webview_t w, const char *html) {
-    struct cocoa_webview* wv = (struct cocoa_webview*)w;
-    ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.window, sel_registerName("loadHTMLString:"),
-                 get_nsstring(html));
+WEBVIEW_API double webview_get_zoom_level(webview_t w) {
+    // Zoom levels are not supported on Cocoa yet.
+    return -1;
+}
+
+WEBVIEW_API void webview_set_html(webview_t w, const char *html,
+                                  const char *base_url) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id baseURL = base_url == NULL
+                   ? nil
+                   : ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURL"),
+                                                        sel_registerName("URLWithString:"),
+                                                        get_nsstring(base_url));
+  ((id(*)(id, SEL, id, id))objc_msgSend)(wv->priv.webview,
+               sel_registerName("loadHTMLString:baseURL:"), get_nsstring(html), baseURL);
+}
+
+WEBVIEW_API void webview_navigate(webview_t w, const char *url) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id nsURL = ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURL"),
+                          sel_registerName("URLWithString:"), get_nsstring(url));
+  ((id(*)(id, SEL, id))objc_msgSend)(wv->priv.webview, sel_registerName("loadRequest:"),
+               ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURLRequest"),
+                            sel_registerName("requestWithURL:"), nsURL));
+}
+
+WEBVIEW_API void webview_load_file(webview_t w, const char *url,
+                                   const char *allowed_dir) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id nsURL = ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURL"),
+                          sel_registerName("URLWithString:"), get_nsstring(url));
+  id readAccessURL = ((id(*)(id, SEL, id))objc_msgSend)((id)objc_getClass("NSURL"),
+                          sel_registerName("fileURLWithPath:"), get_nsstring(allowed_dir));
+  ((id(*)(id, SEL, id, id))objc_msgSend)(wv->priv.webview,
+               sel_registerName("loadFileURL:allowingReadAccessToURL:"), nsURL,
+               readAccessURL);
+}
+
+WEBVIEW_API void webview_reload(webview_t w, int bypass_cache) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  ((id(*)(id, SEL))objc_msgSend)(
+      wv->priv.webview, sel_registerName(bypass_cache ? "reloadFromOrigin" : "reload"));
+}
+
+WEBVIEW_API void webview_go_back(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  ((id(*)(id, SEL))objc_msgSend)(wv->priv.webview, sel_registerName("goBack"));
+}
+
+WEBVIEW_API void webview_go_forward(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  ((id(*)(id, SEL))objc_msgSend)(wv->priv.webview, sel_registerName("goForward"));
+}
+
+WEBVIEW_API int webview_can_go_back(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  return ((BOOL(*)(id, SEL))objc_msgSend)(wv->priv.webview, sel_registerName("canGoBack"));
+}
+
+WEBVIEW_API int webview_can_go_forward(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  return ((BOOL(*)(id, SEL))objc_msgSend)(wv->priv.webview, sel_registerName("canGoForward"));
+}
+
+WEBVIEW_API void webview_stop_loading(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  ((id(*)(id, SEL))objc_msgSend)(wv->priv.webview, sel_registerName("stopLoading"));
+}
+
+static const char *get_cstring(id nsstring) {
+  if (nsstring == NULL) {
+    return NULL;
+  }
+  return ((const char *(*)(id, SEL))objc_msgSend)(nsstring, sel_registerName("UTF8String"));
+}
+
+WEBVIEW_API const char *webview_get_url(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id url = ((id(*)(id, SEL))objc_msgSend)(wv->priv.webview, sel_registerName("URL"));
+  if (url == NULL) {
+    return NULL;
+  }
+  return get_cstring(((id(*)(id, SEL))objc_msgSend)(url, sel_registerName("absoluteString")));
+}
+
+WEBVIEW_API const char *webview_get_page_title(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  return get_cstring(((id(*)(id, SEL))objc_msgSend)(wv->priv.webview, sel_registerName("title")));
+}
+
+WEBVIEW_API void webview_set_load_cb(webview_t w, webview_load_cb_t load_cb) {
+  // Load events are not reported on Cocoa yet.
+}
+
+WEBVIEW_API double webview_get_load_progress(webview_t w) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  return ((double(*)(id, SEL))objc_msgSend)(wv->priv.webview,
+                                            sel_registerName("estimatedProgress"));
+}
+
+WEBVIEW_API void webview_set_navigation_cb(webview_t w,
+                                           webview_navigation_cb_t navigation_cb) {
+  // Navigation policies are not supported on Cocoa yet, every navigation is allowed.
+}
+
+WEBVIEW_API int webview_add_timer(webview_t w, int interval_ms,
+                                  webview_timer_fn fn, void *arg,
+                                  webview_close_fn close) {
+  // Timers are not supported on Cocoa yet.
+  close(arg);
+  return -1;
+}
+
+WEBVIEW_API void webview_remove_timer(webview_t w, int id) {}
+
+WEBVIEW_API void webview_set_new_window_cb(webview_t w,
+                                           webview_new_window_cb_t new_window_cb) {
+  // Pages opening windows are not handled on Cocoa yet.
+}
+
+WEBVIEW_API void webview_set_window_event_cb(webview_t w,
+                                             webview_window_event_cb_t window_event_cb) {
+  // Window events are not reported on Cocoa yet.
+}
+
+WEBVIEW_API int webview_add_style_sheet(webview_t w, const char *css,
+                                        int level) {
+  // User style sheets are not supported on Cocoa.
+  return -1;
+}
+
+WEBVIEW_API void webview_remove_style_sheet(webview_t w, int id) {}
+
+WEBVIEW_API int webview_register_scheme(webview_t w, const char *scheme,
+                                        webview_scheme_cb_t scheme_cb) {
+  // Custom URI schemes are not supported on Cocoa yet, so there are never any
+  // requests to answer.
+  return -1;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_uri(webview_scheme_request_t request) {
+  return NULL;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_scheme(webview_scheme_request_t request) {
+  return NULL;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_method(webview_scheme_request_t request) {
+  return NULL;
+}
+
+WEBVIEW_API void
+webview_scheme_request_get_headers(webview_scheme_request_t request,
+                                   webview_header_cb_t cb, void *arg) {}
+
+WEBVIEW_API void webview_scheme_request_get_body(webview_scheme_request_t request,
+                                                 webview_body_cb_t cb, void *arg) {}
+
+WEBVIEW_API int webview_scheme_request_finish(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, const void *data, size_t len) {
+  return 0;
+}
+
+WEBVIEW_API int webview_scheme_request_finish_stream(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, int64_t length, webview_read_fn read,
+    webview_close_fn close, void *arg) {
+  return 0;
+}
+
+WEBVIEW_API int webview_add_user_script(webview_t w, const char *js,
+                                        int inject_at, int all_frames) {
+  struct cocoa_webview* wv = (struct cocoa_webview*)w;
+  id config = ((id(*)(id, SEL))objc_msgSend)(wv->priv.webview,
+                                             sel_registerName("configuration"));
+  id userController = ((id(*)(id, SEL))objc_msgSend)(
+      config, sel_registerName("userContentController"));
+  id script = ((id(*)(id, SEL))objc_msgSend)((id)objc_getClass("WKUserScript"),
+                                             sel_registerName("alloc"));
+  ((id(*)(id, SEL, id, long, BOOL))objc_msgSend)(
+      script, sel_registerName("initWithSource:injectionTime:forMainFrameOnly:"),
+      get_nsstring(js),
+      inject_at == WEBVIEW_INJECT_AT_DOCUMENT_END
+          ? WKUserScriptInjectionTimeAtDocumentEnd
+          : WKUserScriptInjectionTimeAtDocumentStart,
+      !all_frames);
+  ((id(*)(id, SEL, id))objc_msgSend)(userController, sel_registerName("addUserScript:"),
+                                     script);
+  ((id(*)(id, SEL))objc_msgSend)(script, sel_registerName("release"));
+  return 0;
 }
 
 static void webview_dispatch_cb(void *arg) {
@@ -32,6 +32,71 @@ static inline BSTR webview_to_bstr(const char *s) {
   return bs;
 }
 
+static HICON create_icon(const uint8_t *data, size_t len, int width, int height)
+{
+    if (width > 0 && height > 0) {
+        if (len < static_cast<size_t>(width) * height * 4) {
+            return nullptr;
+        }
+        BITMAPV5HEADER header = {};
+        header.bV5Size = sizeof(header);
+        header.bV5Width = width;
+        // Negative for rows from top to bottom, like the RGBA pixels.
+        header.bV5Height = -height;
+        header.bV5Planes = 1;
+        header.bV5BitCount = 32;
+        header.bV5Compression = BI_BITFIELDS;
+        header.bV5RedMask = 0x00FF0000;
+        header.bV5GreenMask = 0x0000FF00;
+        header.bV5BlueMask = 0x000000FF;
+        header.bV5AlphaMask = 0xFF000000;
+        uint8_t *bits = nullptr;
+        HDC hdc = GetDC(nullptr);
+        HBITMAP color = CreateDIBSection(hdc, reinterpret_cast<BITMAPINFO*>(&header),
+            DIB_RGB_COLORS, reinterpret_cast<void**>(&bits), nullptr, 0);
+        ReleaseDC(nullptr, hdc);
+        if (!color) {
+            return nullptr;
+        }
+        for (int i = 0; i < width * height; i++) {
+            bits[i * 4] = data[i * 4 + 2];
+            bits[i * 4 + 1] = data[i * 4 + 1];
+            bits[i * 4 + 2] = data[i * 4];
+            bits[i * 4 + 3] = data[i * 4 + 3];
+        }
+        HBITMAP mask = CreateBitmap(width, height, 1, 1, nullptr);
+        ICONINFO info = { TRUE, 0, 0, mask, color };
+        HICON icon = CreateIconIndirect(&info);
+        DeleteObject(mask);
+        DeleteObject(color);
+        return icon;
+    }
+    size_t offset = 0;
+    size_t size = len;
+    if (len >= 6 && data[0] == 0 && data[1] == 0 && data[2] == 1 && data[3] == 0) {
+        // An ICO file holds several images, the largest one is used.
+        int count = data[4] | (data[5] << 8);
+        int largest = -1;
+        for (int i = 0; i < count && 6 + static_cast<size_t>(i + 1) * 16 <= len; i++) {
+            const uint8_t *entry = data + 6 + i * 16;
+            int entry_width = entry[0] == 0 ? 256 : entry[0];
+            if (entry_width > largest) {
+                largest = entry_width;
+                size = entry[8] | (entry[9] << 8) | (entry[10] << 16)
+                    | (static_cast<size_t>(entry[11]) << 24);
+                offset = entry[12] | (entry[13] << 8) | (entry[14] << 16)
+                    | (static_cast<size_t>(entry[15]) << 24);
+            }
+        }
+        if (largest < 0 || offset >= len || size > len - offset) {
+            return nullptr;
+        }
+    }
+    // Also decodes PNG images.
+    return CreateIconFromResourceEx(const_cast<PBYTE>(data + offset), static_cast<DWORD>(size),
+        TRUE, 0x00030000, 0, 0, LR_DEFAULTCOLOR);
+}
+
 namespace webview {
 using dispatch_fn_t = std::function<void()>;
 using msg_cb_t = std::function<void(const char* msg)>;
@@ -330,6 +395,83 @@ public:
         SetClassLongPtr(this->m_window, GCLP_HBRBACKGROUND, (LONG_PTR)brush);
     }
 
+    void set_always_on_top(bool always_on_top)
+    {
+        SetWindowPos(this->m_window, always_on_top ? HWND_TOPMOST : HWND_NOTOPMOST, 0, 0, 0, 0,
+            SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE);
+    }
+
+    void set_icon(HICON icon)
+    {
+        SendMessage(this->m_window, WM_SETICON, ICON_BIG, reinterpret_cast<LPARAM>(icon));
+        SendMessage(this->m_window, WM_SETICON, ICON_SMALL, reinterpret_cast<LPARAM>(icon));
+        if (this->m_icon) {
+            DestroyIcon(this->m_icon);
+        }
+        this->m_icon = icon;
+    }
+
+    void get_size(int *width, int *height)
+    {
+        int dpi = MyGetDpiForWindow(this->m_window);
+        if (dpi == 0) {
+            dpi = 96;
+        }
+        RECT r;
+        GetClientRect(this->m_window, &r);
+        *width = MulDiv(r.right - r.left, 96, dpi);
+        *height = MulDiv(r.bottom - r.top, 96, dpi);
+    }
+
+    void set_size(int width, int height)
+    {
+        int dpi = MyGetDpiForWindow(this->m_window);
+        if (dpi == 0) {
+            dpi = 96;
+        }
+        RECT r;
+        r.left = 0;
+        r.top = 0;
+        r.right = MulDiv(width, dpi, 96);
+        r.bottom = MulDiv(height, dpi, 96);
+        AdjustWindowRect(&r, GetWindowLong(this->m_window, GWL_STYLE), 0);
+        SetWindowPos(this->m_window, nullptr, 0, 0, r.right - r.left, r.bottom - r.top,
+            SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE);
+    }
+
+    void get_position(int *x, int *y)
+    {
+        RECT r;
+        GetWindowRect(this->m_window, &r);
+        *x = r.left;
+        *y = r.top;
+    }
+
+    void set_position(int x, int y)
+    {
+        SetWindowPos(this->m_window, nullptr, x, y, 0, 0,
+            SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+    }
+
+    void center()
+    {
+        MONITORINFO monitor_info;
+        monitor_info.cbSize = sizeof(monitor_info);
+        GetMonitorInfo(MonitorFromWindow(this->m_window, MONITOR_DEFAULTTONEAREST),
+            &monitor_info);
+        RECT area = monitor_info.rcWork;
+        RECT r;
+        GetWindowRect(this->m_window, &r);
+        set_position(area.left + ((area.right - area.left) - (r.right - r.left)) / 2,
+            area.top + ((area.bottom - area.top) - (r.bottom - r.top)) / 2);
+    }
+
+    void set_max_size(int width, int height)
+    {
+        this->m_max_width = width;
+        this->m_max_height = height;
+    }
+
     int get_min_width() {
         return this->m_min_width;
     }
@@ -338,6 +480,14 @@ public:
         return this->m_min_height;
     }
 
+    int get_max_width() {
+        return this->m_max_width;
+    }
+
+    int get_max_height() {
+        return this->m_max_height;
+    }
+
     bool get_hide_instead_of_close() {
         return this->m_hide_instead_of_close;
     }
@@ -355,6 +505,8 @@ public:
     RECT saved_rect;
 
     int m_min_width, m_min_height;
+    int m_max_width = -1, m_max_height = -1;
+    HICON m_icon = nullptr;
     bool m_hide_instead_of_close = false;
 };
 
@@ -390,6 +542,12 @@ LRESULT CALLBACK WebviewWndProc(HWND hwnd, UINT msg, WPARAM wp, LPARAM lp)
             LPMINMAXINFO lpMMI = reinterpret_cast<LPMINMAXINFO>(lp);
             lpMMI->ptMinTrackSize.x = w->get_min_width();
             lpMMI->ptMinTrackSize.y = w->get_min_height();
+            if (w->get_max_width() >= 0) {
+                lpMMI->ptMaxTrackSize.x = w->get_max_width();
+            }
+            if (w->get_max_height() >= 0) {
+                lpMMI->ptMaxTrackSize.y = w->get_max_height();
+            }
         }
 
         break;
@@ -455,6 +613,44 @@ public:
     {
         m_webview.NavigateToString(winrt::to_hstring(html));
     }
+    void reload()
+    {
+        m_webview.Refresh();
+    }
+    void go_back()
+    {
+        m_webview.GoBack();
+    }
+    void go_forward()
+    {
+        m_webview.GoForward();
+    }
+    bool can_go_back()
+    {
+        return m_webview.CanGoBack();
+    }
+    bool can_go_forward()
+    {
+        return m_webview.CanGoForward();
+    }
+    void stop()
+    {
+        m_webview.Stop();
+    }
+    const char* url()
+    {
+        Uri source = m_webview.Source();
+        if (source == nullptr) {
+            return nullptr;
+        }
+        m_string = winrt::to_string(source.AbsoluteUri());
+        return m_string.c_str();
+    }
+    const char* title()
+    {
+        m_string = winrt::to_string(m_webview.DocumentTitle());
+        return m_string.c_str();
+    }
     void init(const char* js)
     {
 
@@ -477,6 +673,16 @@ public:
 
     void* get_user_data() { return this->user_data; }
     void set_user_data(void* user_data) { this->user_data = user_data; }
+
+    void* get_view_data() { return this->view_data; }
+    void set_view_data(void* view_data, webview_close_fn view_data_free)
+    {
+        if (this->view_data_free != nullptr) {
+            this->view_data_free(this->view_data);
+        }
+        this->view_data = view_data;
+        this->view_data_free = view_data_free;
+    }
 private:
     void on_message(const char* msg)
     {
@@ -494,8 +700,11 @@ private:
     WebViewControlProcess m_process;
     WebViewControl m_webview = nullptr;
     std::string init_js = "";
+    std::string m_string;
 
     void* user_data = nullptr;
+    void* view_data = nullptr;
+    webview_close_fn view_data_free = nullptr;
     webview_external_invoke_cb_t invoke_cb = nullptr;
 };
 
@@ -522,12 +731,29 @@ WEBVIEW_API int webview_loop(webview_t w, int blocking)
     return static_cast<webview::webview*>(w)->loop(blocking);
 }
 
+WEBVIEW_API int webview_loop_timeout(webview_t w, int timeout_ms)
+{
+    if (MsgWaitForMultipleObjectsEx(0, nullptr, timeout_ms, QS_ALLINPUT,
+                                    MWMO_INPUTAVAILABLE) == WAIT_TIMEOUT) {
+        return 0;
+    }
+    return static_cast<webview::webview*>(w)->loop(0);
+}
+
 WEBVIEW_API int webview_eval(webview_t w, const char *js)
 {
     static_cast<webview::webview*>(w)->eval(js);
     return 0;
 }
 
+WEBVIEW_API int webview_eval_with_result(webview_t w, const char *js,
+                                         webview_eval_result_cb_t cb,
+                                         void *arg)
+{
+    // Evaluating JavaScript with a result is not supported on EdgeHTML yet.
+    return -1;
+}
+
 WEBVIEW_API void webview_set_title(webview_t w, const char *title)
 {
     static_cast<webview::webview*>(w)->set_title(title);
@@ -553,6 +779,111 @@ WEBVIEW_API void webview_set_visible(webview_t w, int visible)
     static_cast<webview::webview*>(w)->set_visible(visible);
 }
 
+WEBVIEW_API void webview_set_always_on_top(webview_t w, int always_on_top)
+{
+    static_cast<webview::webview*>(w)->set_always_on_top(always_on_top);
+}
+
+WEBVIEW_API void webview_set_skip_taskbar(webview_t w, int skip)
+{
+    // Hiding windows from the taskbar is not supported on EdgeHTML yet.
+}
+
+WEBVIEW_API void webview_set_skip_pager(webview_t w, int skip)
+{
+    // There is no pager on Windows.
+}
+
+WEBVIEW_API void webview_set_sticky(webview_t w, int sticky)
+{
+    // Showing windows on all virtual desktops is not supported on EdgeHTML yet.
+}
+
+WEBVIEW_API void webview_set_window_type(webview_t w, int type)
+{
+    // Window types are not supported on EdgeHTML yet.
+}
+
+WEBVIEW_API int webview_set_icon(webview_t w, const uint8_t *data, size_t len,
+                                 int width, int height)
+{
+    HICON icon = create_icon(data, len, width, height);
+    if (!icon) {
+        return -1;
+    }
+    static_cast<webview::webview*>(w)->set_icon(icon);
+    return 0;
+}
+
+WEBVIEW_API void webview_get_size(webview_t w, int *width, int *height)
+{
+    static_cast<webview::webview*>(w)->get_size(width, height);
+}
+
+WEBVIEW_API void webview_set_size(webview_t w, int width, int height)
+{
+    static_cast<webview::webview*>(w)->set_size(width, height);
+}
+
+WEBVIEW_API void webview_get_position(webview_t w, int *x, int *y)
+{
+    static_cast<webview::webview*>(w)->get_position(x, y);
+}
+
+WEBVIEW_API void webview_set_position(webview_t w, int x, int y)
+{
+    static_cast<webview::webview*>(w)->set_position(x, y);
+}
+
+WEBVIEW_API void webview_center(webview_t w)
+{
+    static_cast<webview::webview*>(w)->center();
+}
+
+WEBVIEW_API void webview_set_max_size(webview_t w, int width, int height)
+{
+    static_cast<webview::webview*>(w)->set_max_size(width, height);
+}
+
+WEBVIEW_API void webview_set_aspect_ratio(webview_t w, double ratio)
+{
+    // Aspect ratios are not enforced on EdgeHTML yet.
+}
+
+struct monitor_list {
+    webview_rect *work_areas;
+    int max_count;
+    int count;
+};
+
+static BOOL CALLBACK webview_monitor_cb(HMONITOR monitor, HDC hdc, LPRECT rect, LPARAM arg)
+{
+    auto list = reinterpret_cast<monitor_list*>(arg);
+    if (list->count < list->max_count) {
+        MONITORINFO monitor_info;
+        monitor_info.cbSize = sizeof(monitor_info);
+        GetMonitorInfo(monitor, &monitor_info);
+        RECT area = monitor_info.rcWork;
+        list->work_areas[list->count] = {
+            area.left, area.top, area.right - area.left, area.bottom - area.top
+        };
+    }
+    list->count++;
+    return TRUE;
+}
+
+WEBVIEW_API int webview_get_monitors(webview_t w, webview_rect *work_areas, int max_count)
+{
+    monitor_list list = { work_areas, max_count, 0 };
+    EnumDisplayMonitors(nullptr, nullptr, webview_monitor_cb, reinterpret_cast<LPARAM>(&list));
+    return list.count;
+}
+
+WEBVIEW_API void webview_set_transparent(webview_t w, int transparent)
+{
+    // Transparent windows are not supported on EdgeHTML yet.
+}
+
 WEBVIEW_API void webview_set_color(webview_t w, uint8_t r, uint8_t g,
                                    uint8_t b, uint8_t a)
 {
@@ -563,10 +894,180 @@ WEBVIEW_API void webview_set_zoom_level(webview_t w, const double percentage) {
     // Ignored on EdgeHTML
 }
 
-WEBVIEW_API void webview_set_html(webview_t w, const char *html) {
+WEBVIEW_API double webview_get_zoom_level(webview_t w) {
+    // Zoom levels are not supported on EdgeHTML yet.
+    return -1;
+}
+
+WEBVIEW_API void webview_set_html(webview_t w, const char *html,
+                                  const char *base_url) {
+    // NavigateToString has no base URL, relative URLs can't be resolved.
     static_cast<webview::webview*>(w)->set_html(html);
 }
 
+WEBVIEW_API void webview_navigate(webview_t w, const char *url)
+{
+    static_cast<webview::webview*>(w)->navigate(url);
+}
+
+WEBVIEW_API void webview_load_file(webview_t w, const char *url,
+                                   const char *allowed_dir)
+{
+    // EdgeHTML can't restrict reads to a directory.
+    webview_navigate(w, url);
+}
+
+WEBVIEW_API void webview_reload(webview_t w, int bypass_cache)
+{
+    // EdgeHTML always revalidates cached resources on refresh.
+    static_cast<webview::webview*>(w)->reload();
+}
+
+WEBVIEW_API void webview_go_back(webview_t w)
+{
+    static_cast<webview::webview*>(w)->go_back();
+}
+
+WEBVIEW_API void webview_go_forward(webview_t w)
+{
+    static_cast<webview::webview*>(w)->go_forward();
+}
+
+WEBVIEW_API int webview_can_go_back(webview_t w)
+{
+    return static_cast<webview::webview*>(w)->can_go_back();
+}
+
+WEBVIEW_API int webview_can_go_forward(webview_t w)
+{
+    return static_cast<webview::webview*>(w)->can_go_forward();
+}
+
+WEBVIEW_API void webview_stop_loading(webview_t w)
+{
+    static_cast<webview::webview*>(w)->stop();
+}
+
+WEBVIEW_API const char *webview_get_url(webview_t w)
+{
+    return static_cast<webview::webview*>(w)->url();
+}
+
+WEBVIEW_API const char *webview_get_page_title(webview_t w)
+{
+    return static_cast<webview::webview*>(w)->title();
+}
+
+WEBVIEW_API void webview_set_load_cb(webview_t w, webview_load_cb_t load_cb) {
+    // Load events are not reported on EdgeHTML yet.
+}
+
+WEBVIEW_API double webview_get_load_progress(webview_t w) {
+    // Load progress is not reported on EdgeHTML yet.
+    return 1.0;
+}
+
+WEBVIEW_API void webview_set_navigation_cb(webview_t w,
+                                           webview_navigation_cb_t navigation_cb) {
+    // Navigation policies are not supported on EdgeHTML yet, every navigation is allowed.
+}
+
+WEBVIEW_API int webview_add_timer(webview_t w, int interval_ms,
+                                  webview_timer_fn fn, void *arg,
+                                  webview_close_fn close)
+{
+    // Timers are not supported on EdgeHTML yet.
+    close(arg);
+    return -1;
+}
+
+WEBVIEW_API void webview_remove_timer(webview_t w, int id) {}
+
+WEBVIEW_API void webview_set_new_window_cb(webview_t w,
+                                           webview_new_window_cb_t new_window_cb) {
+    // Pages opening windows are not handled on EdgeHTML yet.
+}
+
+WEBVIEW_API void webview_set_window_event_cb(webview_t w,
+                                             webview_window_event_cb_t window_event_cb) {
+    // Window events are not reported on EdgeHTML yet.
+}
+
+WEBVIEW_API int webview_add_style_sheet(webview_t w, const char *css,
+                                        int level)
+{
+    // User style sheets are not supported on EdgeHTML.
+    return -1;
+}
+
+WEBVIEW_API void webview_remove_style_sheet(webview_t w, int id) {}
+
+WEBVIEW_API int webview_register_scheme(webview_t w, const char *scheme,
+                                        webview_scheme_cb_t scheme_cb)
+{
+    // Custom URI schemes are not supported on EdgeHTML yet, so there are never any
+    // requests to answer.
+    return -1;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_uri(webview_scheme_request_t request)
+{
+    return NULL;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_scheme(webview_scheme_request_t request)
+{
+    return NULL;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_method(webview_scheme_request_t request)
+{
+    return NULL;
+}
+
+WEBVIEW_API void
+webview_scheme_request_get_headers(webview_scheme_request_t request,
+                                   webview_header_cb_t cb, void *arg) {}
+
+WEBVIEW_API void webview_scheme_request_get_body(webview_scheme_request_t request,
+                                                 webview_body_cb_t cb, void *arg) {}
+
+WEBVIEW_API int webview_scheme_request_finish(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, const void *data, size_t len)
+{
+    return 0;
+}
+
+WEBVIEW_API int webview_scheme_request_finish_stream(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, int64_t length, webview_read_fn read,
+    webview_close_fn close, void *arg)
+{
+    return 0;
+}
+
+WEBVIEW_API int webview_add_user_script(webview_t w, const char *js,
+                                        int inject_at, int all_frames)
+{
+    // Initialize scripts only run in the top frame.
+    if (inject_at == WEBVIEW_INJECT_AT_DOCUMENT_END) {
+        std::string deferred = "var f=function(){";
+        deferred.append(js).append(
+            "};document.readyState==='loading'?"
+            "document.addEventListener('DOMContentLoaded',f):f();");
+        static_cast<webview::webview*>(w)->init(deferred.c_str());
+    } else {
+        static_cast<webview::webview*>(w)->init(js);
+    }
+    return 0;
+}
+
 WEBVIEW_API void webview_dispatch(webview_t w, webview_dispatch_fn fn,
                                   void *arg)
 {
@@ -594,23 +1095,43 @@ WEBVIEW_API void* webview_get_user_data(webview_t w)
     return static_cast<webview::webview*>(w)->get_user_data();
 }
 
+WEBVIEW_API int webview_set_view_data(webview_t w, void *data, webview_close_fn free_fn)
+{
+    static_cast<webview::webview*>(w)->set_view_data(data, free_fn);
+    return 0;
+}
+
+WEBVIEW_API void* webview_get_view_data(webview_t w)
+{
+    return static_cast<webview::webview*>(w)->get_view_data();
+}
+
 WEBVIEW_API void* webview_get_window_handle(webview_t w)
 {
     return static_cast<webview::webview*>(w)->window();
 }
 
+WEBVIEW_API int webview_init(void)
+{
+    // EdgeHTML has nothing to set up before the first window.
+    return 0;
+}
+
 WEBVIEW_API webview_t webview_new(
     const char* title, const char* url, int width, int height, int resizable, int debug,
     int frameless, int visible, int min_width, int min_height, int hide_instead_of_close, webview_external_invoke_cb_t external_invoke_cb, void* userdata)
 {
     auto w = new webview::webview(external_invoke_cb, title, width, height, resizable, debug, frameless, visible, min_width, min_height, hide_instead_of_close);
     w->set_user_data(userdata);
-    w->navigate(url);
+    if (url != NULL) {
+        w->navigate(url);
+    }
 	return w;
 }
 
 WEBVIEW_API void webview_free(webview_t w)
 {
+    static_cast<webview::webview*>(w)->set_view_data(nullptr, nullptr);
     delete static_cast<webview::webview*>(w);
 }
 
@@ -33,9 +33,13 @@ struct mshtml_webview {
   int frameless;
   int min_width;
   int min_height;
+  int max_width;
+  int max_height;
   int hide_instead_of_close;
   webview_external_invoke_cb_t external_invoke_cb;
   void *userdata;
+  void *view_data;
+  webview_close_fn view_data_free;
   HWND hwnd;
   IOleObject **browser;
   BOOL is_fullscreen;
@@ -43,13 +47,36 @@ struct mshtml_webview {
   DWORD saved_style;
   DWORD saved_ex_style;
   RECT saved_rect;
+  HICON icon;
+  char *string_buf;
 };
 
 LRESULT CALLBACK wndproc(HWND hwnd, UINT uMsg, WPARAM wParam, LPARAM lParam);
 static BOOL EnableDpiAwareness();
 static int DisplayHTMLPage(struct mshtml_webview *wv);
 
+WEBVIEW_API int webview_set_view_data(webview_t w, void *data,
+                                      webview_close_fn free_fn) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  if (wv->view_data_free != NULL) {
+    wv->view_data_free(wv->view_data);
+  }
+  wv->view_data = data;
+  wv->view_data_free = free_fn;
+  return 0;
+}
+
+WEBVIEW_API void* webview_get_view_data(webview_t w) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  return wv->view_data;
+}
+
 WEBVIEW_API void webview_free(webview_t w) {
+  webview_set_view_data(w, NULL, NULL);
+	if (((struct mshtml_webview*)w)->icon != NULL) {
+		DestroyIcon(((struct mshtml_webview*)w)->icon);
+	}
+	free(((struct mshtml_webview*)w)->string_buf);
 	free(w);
 }
 
@@ -103,6 +130,11 @@ static int webview_fix_ie_compat_mode() {
 
 static const TCHAR *classname = "WebView";
 
+WEBVIEW_API int webview_init(void) {
+  // MSHTML has nothing to set up before the first window.
+  return 0;
+}
+
 WEBVIEW_API webview_t webview_new(
   const char* title, const char* url, int width, int height, int resizable, int debug,
   int frameless, int visible, int min_width, int min_height, int hide_instead_of_close, webview_external_invoke_cb_t external_invoke_cb, void* userdata) {
@@ -183,6 +215,8 @@ WEBVIEW_API webview_t webview_new(
   wv->frameless = frameless;
   wv->min_width = min_width;
   wv->min_height = min_height;
+  wv->max_width = -1;
+  wv->max_height = -1;
   wv->hide_instead_of_close = hide_instead_of_close;
   wv->external_invoke_cb = external_invoke_cb;
   wv->userdata = userdata;
@@ -204,7 +238,9 @@ WEBVIEW_API webview_t webview_new(
     SetWindowLongPtr(wv->hwnd, GWL_STYLE, style);
   }
 
-  DisplayHTMLPage(wv);
+  if (wv->url != NULL) {
+    DisplayHTMLPage(wv);
+  }
 
   ShowWindow(wv->hwnd, visible ? SW_SHOWDEFAULT : SW_HIDE);
   UpdateWindow(wv->hwnd);
@@ -960,6 +996,12 @@ LRESULT CALLBACK wndproc(HWND hwnd, UINT uMsg, WPARAM wParam, LPARAM lParam) {
       LPMINMAXINFO lpMMI = (LPMINMAXINFO)lParam;
       lpMMI->ptMinTrackSize.x = wv->min_width;
       lpMMI->ptMinTrackSize.y = wv->min_height;
+      if (wv->max_width >= 0) {
+        lpMMI->ptMaxTrackSize.x = wv->max_width;
+      }
+      if (wv->max_height >= 0) {
+        lpMMI->ptMaxTrackSize.y = wv->max_height;
+      }
     }
 
     break;
@@ -1016,6 +1058,14 @@ WEBVIEW_API int webview_loop(webview_t w, int blocking) {
   return 0;
 }
 
+WEBVIEW_API int webview_loop_timeout(webview_t w, int timeout_ms) {
+  if (MsgWaitForMultipleObjectsEx(0, NULL, timeout_ms, QS_ALLINPUT,
+                                  MWMO_INPUTAVAILABLE) == WAIT_TIMEOUT) {
+    return 0;
+  }
+  return webview_loop(w, 0);
+}
+
 WEBVIEW_API int webview_eval(webview_t w, const char *js) {
   struct mshtml_webview* wv = (struct mshtml_webview*)w;
   IWebBrowser2 *webBrowser2;
@@ -1083,6 +1133,13 @@ WEBVIEW_API int webview_eval(webview_t w, const char *js) {
   return 0;
 }
 
+WEBVIEW_API int webview_eval_with_result(webview_t w, const char *js,
+                                         webview_eval_result_cb_t cb,
+                                         void *arg) {
+  // Evaluating JavaScript with a result is not supported on MSHTML yet.
+  return -1;
+}
+
 WEBVIEW_API void webview_dispatch(webview_t w, webview_dispatch_fn fn,
                                   void *arg) {
   struct mshtml_webview* wv = (struct mshtml_webview*)w;
@@ -1182,6 +1239,218 @@ WEBVIEW_API void webview_set_visible(webview_t w, int visible) {
   ShowWindow(wv->hwnd, visible ? SW_SHOW : SW_HIDE);
 }
 
+static int webview_dpi() {
+  HDC screen = GetDC(0);
+  int dpi = GetDeviceCaps(screen, LOGPIXELSX);
+  ReleaseDC(0, screen);
+  return dpi;
+}
+
+WEBVIEW_API void webview_get_size(webview_t w, int *width, int *height) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  int dpi = webview_dpi();
+  RECT r;
+  GetClientRect(wv->hwnd, &r);
+  *width = MulDiv(r.right - r.left, 96, dpi);
+  *height = MulDiv(r.bottom - r.top, 96, dpi);
+}
+
+WEBVIEW_API void webview_set_size(webview_t w, int width, int height) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  int dpi = webview_dpi();
+  RECT r;
+  r.left = 0;
+  r.top = 0;
+  r.right = MulDiv(width, dpi, 96);
+  r.bottom = MulDiv(height, dpi, 96);
+  AdjustWindowRect(&r, GetWindowLong(wv->hwnd, GWL_STYLE), 0);
+  SetWindowPos(wv->hwnd, NULL, 0, 0, r.right - r.left, r.bottom - r.top,
+               SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE);
+}
+
+WEBVIEW_API void webview_get_position(webview_t w, int *x, int *y) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  RECT r;
+  GetWindowRect(wv->hwnd, &r);
+  *x = r.left;
+  *y = r.top;
+}
+
+WEBVIEW_API void webview_set_position(webview_t w, int x, int y) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  SetWindowPos(wv->hwnd, NULL, x, y, 0, 0,
+               SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+}
+
+WEBVIEW_API void webview_center(webview_t w) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  MONITORINFO monitor_info;
+  monitor_info.cbSize = sizeof(monitor_info);
+  GetMonitorInfo(MonitorFromWindow(wv->hwnd, MONITOR_DEFAULTTONEAREST),
+                 &monitor_info);
+  RECT area = monitor_info.rcWork;
+  RECT r;
+  GetWindowRect(wv->hwnd, &r);
+  webview_set_position(
+      w, area.left + ((area.right - area.left) - (r.right - r.left)) / 2,
+      area.top + ((area.bottom - area.top) - (r.bottom - r.top)) / 2);
+}
+
+WEBVIEW_API void webview_set_max_size(webview_t w, int width, int height) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  wv->max_width = width;
+  wv->max_height = height;
+}
+
+WEBVIEW_API void webview_set_aspect_ratio(webview_t w, double ratio) {
+  // Aspect ratios are not enforced on MSHTML yet.
+}
+
+struct monitor_list {
+  struct webview_rect *work_areas;
+  int max_count;
+  int count;
+};
+
+static BOOL CALLBACK webview_monitor_cb(HMONITOR monitor, HDC hdc, LPRECT rect,
+                                        LPARAM arg) {
+  struct monitor_list *list = (struct monitor_list *)arg;
+  if (list->count < list->max_count) {
+    MONITORINFO monitor_info;
+    monitor_info.cbSize = sizeof(monitor_info);
+    GetMonitorInfo(monitor, &monitor_info);
+    RECT area = monitor_info.rcWork;
+    list->work_areas[list->count].x = area.left;
+    list->work_areas[list->count].y = area.top;
+    list->work_areas[list->count].width = area.right - area.left;
+    list->work_areas[list->count].height = area.bottom - area.top;
+  }
+  list->count++;
+  return TRUE;
+}
+
+WEBVIEW_API int webview_get_monitors(webview_t w,
+                                     struct webview_rect *work_areas,
+                                     int max_count) {
+  struct monitor_list list = {work_areas, max_count, 0};
+  EnumDisplayMonitors(NULL, NULL, webview_monitor_cb, (LPARAM)&list);
+  return list.count;
+}
+
+WEBVIEW_API void webview_set_always_on_top(webview_t w, int always_on_top) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  SetWindowPos(wv->hwnd, always_on_top ? HWND_TOPMOST : HWND_NOTOPMOST, 0, 0,
+               0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE);
+}
+
+WEBVIEW_API void webview_set_skip_taskbar(webview_t w, int skip) {
+  // Hiding windows from the taskbar is not supported on MSHTML yet.
+}
+
+WEBVIEW_API void webview_set_skip_pager(webview_t w, int skip) {
+  // There is no pager on Windows.
+}
+
+WEBVIEW_API void webview_set_sticky(webview_t w, int sticky) {
+  // Showing windows on all virtual desktops is not supported on MSHTML yet.
+}
+
+WEBVIEW_API void webview_set_window_type(webview_t w, int type) {
+  // Window types are not supported on MSHTML yet.
+}
+
+static HICON webview_create_icon(const uint8_t *data, size_t len, int width,
+                                 int height) {
+  if (width > 0 && height > 0) {
+    if (len < (size_t)width * height * 4) {
+      return NULL;
+    }
+    BITMAPV5HEADER header;
+    ZeroMemory(&header, sizeof(header));
+    header.bV5Size = sizeof(header);
+    header.bV5Width = width;
+    // Negative for rows from top to bottom, like the RGBA pixels.
+    header.bV5Height = -height;
+    header.bV5Planes = 1;
+    header.bV5BitCount = 32;
+    header.bV5Compression = BI_BITFIELDS;
+    header.bV5RedMask = 0x00FF0000;
+    header.bV5GreenMask = 0x0000FF00;
+    header.bV5BlueMask = 0x000000FF;
+    header.bV5AlphaMask = 0xFF000000;
+    uint8_t *bits;
+    HDC hdc = GetDC(NULL);
+    HBITMAP color = CreateDIBSection(hdc, (BITMAPINFO *)&header, DIB_RGB_COLORS,
+                                     (void **)&bits, NULL, 0);
+    ReleaseDC(NULL, hdc);
+    if (color == NULL) {
+      return NULL;
+    }
+    for (int i = 0; i < width * height; i++) {
+      bits[i * 4] = data[i * 4 + 2];
+      bits[i * 4 + 1] = data[i * 4 + 1];
+      bits[i * 4 + 2] = data[i * 4];
+      bits[i * 4 + 3] = data[i * 4 + 3];
+    }
+    HBITMAP mask = CreateBitmap(width, height, 1, 1, NULL);
+    ICONINFO info;
+    info.fIcon = TRUE;
+    info.xHotspot = 0;
+    info.yHotspot = 0;
+    info.hbmMask = mask;
+    info.hbmColor = color;
+    HICON icon = CreateIconIndirect(&info);
+    DeleteObject(mask);
+    DeleteObject(color);
+    return icon;
+  }
+  size_t offset = 0;
+  size_t size = len;
+  if (len >= 6 && data[0] == 0 && data[1] == 0 && data[2] == 1 &&
+      data[3] == 0) {
+    // An ICO file holds several images, the largest one is used.
+    int count = data[4] | (data[5] << 8);
+    int largest = -1;
+    for (int i = 0; i < count && 6 + (size_t)(i + 1) * 16 <= len; i++) {
+      const uint8_t *entry = data + 6 + i * 16;
+      int entry_width = entry[0] == 0 ? 256 : entry[0];
+      if (entry_width > largest) {
+        largest = entry_width;
+        size = entry[8] | (entry[9] << 8) | (entry[10] << 16) |
+               ((size_t)entry[11] << 24);
+        offset = entry[12] | (entry[13] << 8) | (entry[14] << 16) |
+                 ((size_t)entry[15] << 24);
+      }
+    }
+    if (largest < 0 || offset >= len || size > len - offset) {
+      return NULL;
+    }
+  }
+  // Also decodes PNG images.
+  return CreateIconFromResourceEx((PBYTE)data + offset, (DWORD)size, TRUE,
+                                  0x00030000, 0, 0, LR_DEFAULTCOLOR);
+}
+
+WEBVIEW_API int webview_set_icon(webview_t w, const uint8_t *data, size_t len,
+                                 int width, int height) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  HICON icon = webview_create_icon(data, len, width, height);
+  if (icon == NULL) {
+    return -1;
+  }
+  SendMessage(wv->hwnd, WM_SETICON, ICON_BIG, (LPARAM)icon);
+  SendMessage(wv->hwnd, WM_SETICON, ICON_SMALL, (LPARAM)icon);
+  if (wv->icon != NULL) {
+    DestroyIcon(wv->icon);
+  }
+  wv->icon = icon;
+  return 0;
+}
+
+WEBVIEW_API void webview_set_transparent(webview_t w, int transparent) {
+  // Transparent windows are not supported on MSHTML.
+}
+
 WEBVIEW_API void webview_set_color(webview_t w, uint8_t r, uint8_t g,
                                    uint8_t b, uint8_t a) {
   struct mshtml_webview* wv = (struct mshtml_webview*)w;
@@ -1210,11 +1479,226 @@ WEBVIEW_API void webview_set_zoom_level(webview_t w, const double percentage) {
     }
 }
 
-WEBVIEW_API void webview_set_html(webview_t w, const char *html) {
-    struct mshtml_webview* wv = (struct mshtml_webview*)w;
-    wv->url = html;
+WEBVIEW_API double webview_get_zoom_level(webview_t w) {
+    // Reading the zoom level is not supported on MSHTML yet.
+    return -1;
+}
 
-    DisplayHTMLPage(wv);
+WEBVIEW_API void webview_set_html(webview_t w, const char *html,
+                                  const char *base_url) {
+  // The HTML is written into about:blank like a data: URL, so there is no base
+  // URL. DisplayHTMLPage decodes percent escapes, so only '%' needs encoding.
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  size_t len = strlen(WEBVIEW_DATA_URL_PREFIX) + 1;
+  for (const char *p = html; *p; p++) {
+    len += *p == '%' ? 3 : 1;
+  }
+  char *url = (char *)calloc(1, len);
+  char *q = url + strlen(WEBVIEW_DATA_URL_PREFIX);
+  strcpy(url, WEBVIEW_DATA_URL_PREFIX);
+  for (const char *p = html; *p; p++) {
+    if (*p == '%') {
+      strcpy(q, "%25");
+      q += 3;
+    } else {
+      *q++ = *p;
+    }
+  }
+  wv->url = url;
+  DisplayHTMLPage(wv);
+  wv->url = NULL;
+  free(url);
+}
+
+WEBVIEW_API void webview_navigate(webview_t w, const char *url) {
+  struct mshtml_webview* wv = (struct mshtml_webview*)w;
+  wv->url = url;
+
+  DisplayHTMLPage(wv);
+}
+
+WEBVIEW_API void webview_load_file(webview_t w, const char *url,
+                                   const char *allowed_dir) {
+  // MSHTML can't restrict reads to a directory, access to local files is
+  // governed by the security zone settings.
+  webview_navigate(w, url);
+}
+
+static IWebBrowser2 *get_web_browser(struct mshtml_webview *wv) {
+  IWebBrowser2 *webBrowser;
+  IOleObject *browser = *wv->browser;
+  if (browser->lpVtbl->QueryInterface(browser, iid_unref(&IID_IWebBrowser2),
+                                      (void **)&webBrowser) != S_OK) {
+    return NULL;
+  }
+  return webBrowser;
+}
+
+WEBVIEW_API void webview_reload(webview_t w, int bypass_cache) {
+  IWebBrowser2 *webBrowser = get_web_browser((struct mshtml_webview*)w);
+  if (webBrowser != NULL) {
+    VARIANT level;
+    VariantInit(&level);
+    level.vt = VT_I4;
+    level.lVal = bypass_cache ? REFRESH_COMPLETELY : REFRESH_NORMAL;
+    webBrowser->lpVtbl->Refresh2(webBrowser, &level);
+    webBrowser->lpVtbl->Release(webBrowser);
+  }
+}
+
+WEBVIEW_API void webview_go_back(webview_t w) {
+  IWebBrowser2 *webBrowser = get_web_browser((struct mshtml_webview*)w);
+  if (webBrowser != NULL) {
+    webBrowser->lpVtbl->GoBack(webBrowser);
+    webBrowser->lpVtbl->Release(webBrowser);
+  }
+}
+
+WEBVIEW_API void webview_go_forward(webview_t w) {
+  IWebBrowser2 *webBrowser = get_web_browser((struct mshtml_webview*)w);
+  if (webBrowser != NULL) {
+    webBrowser->lpVtbl->GoForward(webBrowser);
+    webBrowser->lpVtbl->Release(webBrowser);
+  }
+}
+
+WEBVIEW_API int webview_can_go_back(webview_t w) {
+  // IWebBrowser2 only reports this through the CommandStateChange event.
+  return 1;
+}
+
+WEBVIEW_API int webview_can_go_forward(webview_t w) {
+  // IWebBrowser2 only reports this through the CommandStateChange event.
+  return 1;
+}
+
+WEBVIEW_API void webview_stop_loading(webview_t w) {
+  IWebBrowser2 *webBrowser = get_web_browser((struct mshtml_webview*)w);
+  if (webBrowser != NULL) {
+    webBrowser->lpVtbl->Stop(webBrowser);
+    webBrowser->lpVtbl->Release(webBrowser);
+  }
+}
+
+static const char *get_location(struct mshtml_webview *wv, int name) {
+  IWebBrowser2 *webBrowser = get_web_browser(wv);
+  BSTR bstr = NULL;
+  if (webBrowser == NULL) {
+    return NULL;
+  }
+  if (name) {
+    webBrowser->lpVtbl->get_LocationName(webBrowser, &bstr);
+  } else {
+    webBrowser->lpVtbl->get_LocationURL(webBrowser, &bstr);
+  }
+  webBrowser->lpVtbl->Release(webBrowser);
+  if (bstr == NULL) {
+    return NULL;
+  }
+  free(wv->string_buf);
+  wv->string_buf = webview_from_utf16(bstr);
+  SysFreeString(bstr);
+  return wv->string_buf;
+}
+
+WEBVIEW_API const char *webview_get_url(webview_t w) {
+  return get_location((struct mshtml_webview*)w, 0);
+}
+
+WEBVIEW_API const char *webview_get_page_title(webview_t w) {
+  return get_location((struct mshtml_webview*)w, 1);
+}
+
+WEBVIEW_API void webview_set_load_cb(webview_t w, webview_load_cb_t load_cb) {
+  // Load events are not reported on MSHTML yet.
+}
+
+WEBVIEW_API double webview_get_load_progress(webview_t w) {
+  // Load progress is not reported on MSHTML yet.
+  return 1.0;
+}
+
+WEBVIEW_API void webview_set_navigation_cb(webview_t w,
+                                           webview_navigation_cb_t navigation_cb) {
+  // Navigation policies are not supported on MSHTML yet, every navigation is allowed.
+}
+
+WEBVIEW_API int webview_add_timer(webview_t w, int interval_ms,
+                                  webview_timer_fn fn, void *arg,
+                                  webview_close_fn close) {
+  // Timers are not supported on MSHTML yet.
+  close(arg);
+  return -1;
+}
+
+WEBVIEW_API void webview_remove_timer(webview_t w, int id) {}
+
+WEBVIEW_API void webview_set_new_window_cb(webview_t w,
+                                           webview_new_window_cb_t new_window_cb) {
+  // Pages opening windows are not handled on MSHTML yet.
+}
+
+WEBVIEW_API void webview_set_window_event_cb(webview_t w,
+                                             webview_window_event_cb_t window_event_cb) {
+  // Window events are not reported on MSHTML yet.
+}
+
+WEBVIEW_API int webview_add_style_sheet(webview_t w, const char *css,
+                                        int level) {
+  // User style sheets are not supported on MSHTML.
+  return -1;
+}
+
+WEBVIEW_API void webview_remove_style_sheet(webview_t w, int id) {}
+
+WEBVIEW_API int webview_register_scheme(webview_t w, const char *scheme,
+                                        webview_scheme_cb_t scheme_cb) {
+  // Custom URI schemes are not supported on MSHTML yet, so there are never any
+  // requests to answer.
+  return -1;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_uri(webview_scheme_request_t request) {
+  return NULL;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_scheme(webview_scheme_request_t request) {
+  return NULL;
+}
+
+WEBVIEW_API const char *
+webview_scheme_request_get_method(webview_scheme_request_t request) {
+  return NULL;
+}
+
+WEBVIEW_API void
+webview_scheme_request_get_headers(webview_scheme_request_t request,
+                                   webview_header_cb_t cb, void *arg) {}
+
+WEBVIEW_API void webview_scheme_request_get_body(webview_scheme_request_t request,
+                                                 webview_body_cb_t cb, void *arg) {}
+
+WEBVIEW_API int webview_scheme_request_finish(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, const void *data, size_t len) {
+  return 0;
+}
+
+WEBVIEW_API int webview_scheme_request_finish_stream(
+    webview_scheme_request_t request, int status, const char *mime,
+    const char *const *header_names, const char *const *header_values,
+    int header_count, int64_t length, webview_read_fn read,
+    webview_close_fn close, void *arg) {
+  return 0;
+}
+
+WEBVIEW_API int webview_add_user_script(webview_t w, const char *js,
+                                        int inject_at, int all_frames) {
+  // MSHTML has no way to run scripts before the page's own.
+  return -1;
 }
 
 WEBVIEW_API void webview_exit(webview_t w) {